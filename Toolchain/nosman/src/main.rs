@@ -62,6 +62,12 @@ fn main() {
              .value_parser(clap::builder::PossibleValuesParser::new(["cpp/cmake"]))
              .default_value("cpp/cmake");
 
+    let signing_key_arg = Arg::new("signing_key")
+             .long("signing-key")
+             .help(format!("Path to the ed25519 secret key to sign the release with (see keygen command).\n\
+             If not provided, {} environment variable is used. Releases are published unsigned if neither is set.", constants::SIGNING_KEY_ENV_VAR))
+             .required(false);
+
     let exe_path = std::env::current_exe().expect("Unable to get current executable path");
     let stem = exe_path.file_stem().expect("Unable to get executable name").to_str().expect("Unable to convert executable name to string");
     let boxed_name = Box::new(stem.to_string());
@@ -76,6 +82,33 @@ fn main() {
             .long("workspace")
             .default_value(".")
         )
+        .arg(Arg::new("offline")
+            .help(format!("Do not access the network. Cached package index and downloads are used instead.\n\
+            Can also be enabled by setting {} environment variable to 1.", constants::OFFLINE_ENV_VAR))
+            .long("offline")
+            .action(ArgAction::SetTrue)
+            .num_args(0)
+            .required(false)
+        )
+        .arg(Arg::new("connect_timeout")
+            .help("Timeout in seconds for connecting to a server")
+            .long("connect-timeout")
+            .value_parser(clap::value_parser!(u64))
+            .default_value("30")
+        )
+        .arg(Arg::new("timeout")
+            .help("Timeout in seconds for a single download attempt. 0 means no timeout.\n\
+            Interrupted downloads are resumed on the next attempt if the server supports it.")
+            .long("timeout")
+            .value_parser(clap::value_parser!(u64))
+            .default_value("0")
+        )
+        .arg(Arg::new("retries")
+            .help("Number of times a failed download is retried, with exponential backoff")
+            .long("retries")
+            .value_parser(clap::value_parser!(u32))
+            .default_value("5")
+        )
         .arg(Arg::new("silently_agree_eula")
             .help("Agrees to Nodos EULA. If multiple engines are installed, it will agree to all of their EULAs.")
             .long("silently-agree-eula")
@@ -96,13 +129,35 @@ fn main() {
         )
         .subcommand(Command::new("install")
             .about("Install a module")
-            .arg(Arg::new("module").required(true))
-            .arg(Arg::new("version").required(false))
+            .arg(Arg::new("module").required(false)
+                .help("Name of the module to install, or 'git+<url>[@<rev>]' to clone it from a git repository.\n\
+                Can be omitted with --locked to install every module in the lockfile."))
+            .arg(Arg::new("from")
+                .help("Install the module in a local archive (zip, tar.gz, tar.xz or tar.zst) or folder instead of downloading it from a remote.\n\
+                Module name and version are read from the module manifest in it.")
+                .long("from")
+                .conflicts_with_all(["module", "version", "locked"])
+                .required(false)
+            )
+            .arg(Arg::new("git")
+                .help("Clone the modules in this git repository instead of downloading them from a remote.\n\
+                Module name can also be given as 'git+<url>[@<rev>]', e.g. git+https://github.com/org/repo.git@v1.2.0")
+                .long("git")
+                .conflicts_with_all(["module", "version", "locked", "from"])
+                .required(false)
+            )
+            .arg(Arg::new("rev")
+                .help("Tag, branch or commit to check out when installing from git. Defaults to the default branch.")
+                .long("rev")
+                .required(false)
+            )
+            .arg(Arg::new("version").required(false)
+                .help("Version requirement, e.g. '1.2', '^1.2', '~1.2.3', '>=1.2, <2', '1.*' or '=1.2.3'.\n\
+                A version without an operator such as '1.2' means 'a.b <= x < a.(b+1)'. If not provided, the latest version is installed."))
             .arg(Arg::new("exact")
                 .action(ArgAction::SetTrue)
-                .help("If not set, version parameter will be interpreted as minimum required version within that minor/patch version.\n\
-                If no version 'x' such that 'a.b <= x < a.(b+1)' is found among installed modules, latest such version will be installed.\n\
-                If version is set to 'latest' or has no minor component, it will fail.")
+                .help("Install exactly the given version instead of interpreting it as a version requirement.\n\
+                Otherwise, if an installed module matches the requirement, it is used. If not, the latest matching version is installed.")
                 .long("exact")
                 .num_args(0)
                 .required(false)
@@ -118,12 +173,133 @@ fn main() {
                 .long("out-dir")
                 .required(false)
             )
+            .arg(Arg::new("locked")
+                .action(ArgAction::SetTrue)
+                .help(format!("Only install module versions recorded in the workspace lockfile ({}), from the recorded URLs.\n\
+                Fails if the lockfile would need to change or a downloaded archive does not match the recorded digest.", constants::LOCKFILE_NAME))
+                .long("locked")
+                .num_args(0)
+                .required(false)
+            )
+            .arg(Arg::new("no_deps")
+                .action(ArgAction::SetTrue)
+                .help("Do not install the dependencies of the module.\n\
+                By default, the latest version matching the requirement of each dependency that is not installed will be installed, recursively.")
+                .long("no-deps")
+                .num_args(0)
+                .required(false)
+            )
+            .arg(Arg::new("pre")
+                .action(ArgAction::SetTrue)
+                .help("Allow pre-release versions (e.g. 1.3.0-rc.1) of the module. By default, they are only installed if the version requirement names one.")
+                .long("pre")
+                .num_args(0)
+                .required(false)
+            )
+            .arg(Arg::new("ignore_api_version")
+                .action(ArgAction::SetTrue)
+                .help("Do not check the plugin/subsystem API versions of the releases against the engines in the workspace.\n\
+                By default, only releases that one of the engines under Engine/ can load are installed.")
+                .long("ignore-api-version")
+                .num_args(0)
+                .required(false)
+            )
+            .arg(Arg::new("platform")
+                .help("Platform of the module binaries to download, e.g. x86_64-windows. Defaults to the current platform.\n\
+                Modules for another platform are downloaded to out_dir with their dependencies, but they are not registered to the workspace \
+                and their API versions are not checked.")
+                .long("platform")
+                .conflicts_with_all(["locked", "from", "git"])
+                .required(false)
+            )
         )
         .subcommand(Command::new("remove")
             .about("Remove a module")
+            .after_help("Fails if another installed module depends on it and no other installed version satisfies that dependency.")
+            .arg(Arg::new("module").required(true))
+            .arg(Arg::new("version").required(true))
+            .arg(Arg::new("force")
+                .action(ArgAction::SetTrue)
+                .long("force")
+                .help("Remove the module even if other installed modules depend on it")
+                .conflicts_with("cascade")
+                .num_args(0)
+                .required(false)
+            )
+            .arg(Arg::new("cascade")
+                .action(ArgAction::SetTrue)
+                .long("cascade")
+                .help("Also remove the installed modules that depend on it, recursively")
+                .num_args(0)
+                .required(false)
+            )
+        )
+        .subcommand(Command::new("autoremove")
+            .about("Remove the modules that were installed as a dependency and are no longer required")
+            .after_help("A module is required if an explicitly installed module depends on it, directly or through other modules. See 'mark' command.")
+            .arg(Arg::new("dry_run")
+                .action(ArgAction::SetTrue)
+                .long("dry-run")
+                .help("Only list the modules that would be removed")
+                .num_args(0)
+                .required(false)
+            )
+        )
+        .subcommand(Command::new("mark")
+            .about("Mark an installed module as explicitly installed or installed as a dependency")
+            .arg(Arg::new("module").required(true))
+            .arg(Arg::new("version").required(false)
+                .help("If not provided, all installed versions of the module are marked"))
+            .arg(Arg::new("explicit")
+                .action(ArgAction::SetTrue)
+                .long("explicit")
+                .help("Keep the module even if no other module depends on it")
+                .num_args(0)
+            )
+            .arg(Arg::new("dependency")
+                .action(ArgAction::SetTrue)
+                .long("dependency")
+                .help("Let autoremove remove the module once no explicitly installed module depends on it")
+                .num_args(0)
+            )
+            .group(clap::ArgGroup::new("marking").args(["explicit", "dependency"]).required(true))
+        )
+        .subcommand(Command::new("tree")
+            .about("Print the dependency tree of installed modules")
+            .after_help("Each dependency is shown with the installed version it resolves to, or marked as missing or version-mismatched.\n\
+            Dependencies of missing modules are taken from the cached package index.")
+            .arg(Arg::new("module").required(false)
+                .help("If not provided, the trees of all modules that no other module depends on are printed"))
+            .arg(Arg::new("version").required(false)
+                .help("If not provided, the trees of all installed versions of the module are printed"))
+        )
+        .subcommand(Command::new("why")
+            .about("Print the chains of installed modules that depend on a module")
             .arg(Arg::new("module").required(true))
             .arg(Arg::new("version").required(true))
         )
+        .subcommand(Command::new("check")
+            .about("Check the installed modules for dependency conflicts and unresolved dependencies")
+            .after_help("A conflict is reported when no single version of a dependency satisfies all installed modules requiring it, \
+            e.g. one module requires nos.sys.vulkan 5.2 and another requires 6.0.\n\
+            Only the newest installed version of each module is taken into account.\n\
+            Exits with an error if any problem is found.")
+        )
+        .subcommand(Command::new("sync")
+            .about(format!("Bring the workspace to the state described in {}", constants::WORKSPACE_MANIFEST_FILE_NAME))
+            .after_help(format!("Adds the listed remotes, brings the listed Nodos release, installs the listed modules (with their dependencies) \
+            and removes the modules that are no longer required by the listed ones.\n\
+            Modules that were not installed from a remote are never removed.\n\
+            Example {}:\n\
+            {{\n  \"nodos\": {{ \"name\": \"nodos\", \"version\": \"1.3\" }},\n  \"modules\": [ {{ \"name\": \"nos.sys.vulkan\", \"version\": \"5.2\" }} ]\n}}", constants::WORKSPACE_MANIFEST_FILE_NAME))
+            .arg(Arg::new("check")
+                .action(ArgAction::SetTrue)
+                .help("Do not change anything, only report the differences. Fails if the workspace is out of sync.")
+                .long("check")
+                .num_args(0)
+                .required(false)
+            )
+        )
         .subcommand(Command::new("rescan")
             .about("Rescan modules and update caches")
             .arg(Arg::new("fetch_index")
@@ -137,6 +313,79 @@ fn main() {
         .subcommand(Command::new("list")
             .about("List installed modules")
         )
+        .subcommand(Command::new("outdated")
+            .about("List installed modules that have newer versions in the index")
+            .after_help("'Compatible' is the newest version with the same major and minor version as the installed one,\n\
+            'Latest' is the newest version overall.")
+        )
+        .subcommand(Command::new("upgrade")
+            .about("Install the newest compatible versions of installed modules")
+            .after_help("A compatible version has the same major and minor version as the installed one. See 'outdated' command.\n\
+            Modules installed from git are pulled if a branch is checked out.")
+            .arg(Arg::new("module")
+                .help("Modules to upgrade. If not provided, all installed modules are upgraded.")
+                .action(ArgAction::Append)
+                .num_args(0..)
+                .required(false)
+            )
+            .arg(Arg::new("remove_old")
+                .action(ArgAction::SetTrue)
+                .long("remove-old")
+                .help("Remove the superseded versions after upgrading")
+                .num_args(0)
+                .required(false)
+            )
+        )
+        .subcommand(Command::new("search")
+            .about("Search packages in the remotes")
+            .after_help("Prints the matching packages with their latest release for the platform.")
+            .arg(Arg::new("text")
+                .help("Text to look for in package names and vendors. If not provided, all packages are listed.")
+                .required(false)
+            )
+            .arg(Arg::new("vendor")
+                .long("vendor")
+                .help("Only list the packages of this vendor")
+                .required(false)
+            )
+            .arg(Arg::new("category")
+                .long("category")
+                .help("Only list the packages in this category")
+                .required(false)
+            )
+            .arg(Arg::new("tag")
+                .action(ArgAction::Append)
+                .long("tag")
+                .help("Only list the packages with this module or release tag. Can be specified multiple times.")
+                .required(false)
+                .num_args(1)
+            )
+            .arg(Arg::new("type")
+                .long("type")
+                .short('t')
+                .value_parser(clap::builder::PossibleValuesParser::new(["plugin", "subsystem", "nodos", "engine", "generic"]))
+                .help("Only list the packages of this type")
+                .required(false)
+            )
+            .arg(Arg::new("platform")
+                .long("platform")
+                .help("Platform of the releases to look for, e.g. x86_64-windows. Platform independent releases are always included. If not provided, the current platform will be used.")
+                .required(false)
+            )
+        )
+        .subcommand(Command::new("releases")
+            .about("List all published releases of a package in the remotes")
+            .arg(Arg::new("package")
+                .help("Name of the package")
+                .required(true)
+            )
+            .arg(Arg::new("json")
+                .long("json")
+                .help("Print the releases as JSON")
+                .action(ArgAction::SetTrue)
+                .required(false)
+            )
+        )
         .subcommand(Command::new("info")
             .about("Returns information about an installed module in JSON format.\n\
             If no such module is installed, it will return an error.")
@@ -144,8 +393,8 @@ fn main() {
             .arg(Arg::new("version").required(true))
             .arg(Arg::new("relaxed")
                 .action(ArgAction::SetTrue)
-                .help("If set, version parameter will be interpreted as minimum required version within that minor/patch version.\n\
-                It will return information about a version 'x' found among installed modules such that 'a.b <= x < a.(b+1)'.")
+                .help("If set, version parameter will be interpreted as a version requirement, e.g. '1.2' ('a.b <= x < a.(b+1)'), '^1.2' or '>=1.2, <2'.\n\
+                It will return information about the latest installed version that matches it.")
                 .long("relaxed")
                 .num_args(0)
                 .required(false)
@@ -154,7 +403,8 @@ fn main() {
         .subcommand(Command::new("sdk-info")
             .about("Returns information about an installed Nodos SDK under workspace.\n\
             If no such version is found, it will return an error.")
-            .arg(Arg::new("version").required(true))
+            .arg(Arg::new("version").required(true)
+                .help("Version requirement, e.g. '1.2', '>=1.2, <2' or '=1.2.3'. A version without an operator matches the same major and an equal or newer minor version, e.g. '1.2.3' matches 1.2.0 and 1.5.1 but not 2.0."))
 			.arg(Arg::new("sdk-type").required(false)
 				.help("Type of the SDK to get information about.")
 				.default_value("engine")
@@ -173,6 +423,28 @@ fn main() {
                 .about("Remove a remote")
                 .arg(Arg::new("url").required(true))
             )
+            .subcommand(Command::new("trust")
+                .about("Trust a public key of a vendor for the releases on a remote")
+                .after_help("Once a remote trusts a key, every release from the remote must be signed with a trusted key of the vendor of the package.\n\
+                Releases of vendors without a trusted key can no longer be installed from that remote.")
+                .arg(Arg::new("remote").help("Name or URL of the remote").required(true))
+                .arg(Arg::new("vendor").required(true))
+                .arg(Arg::new("public_key").help("Base64 encoded ed25519 public key").required(true))
+            )
+        )
+        .subcommand(Command::new("cache")
+            .about("Manage the download cache.")
+            .after_help(format!("Downloaded package archives are kept in a per-user cache, shared by all workspaces.\n\
+            The cache location can be changed with {} environment variable.", constants::CACHE_DIR_ENV_VAR))
+            .subcommand(Command::new("list")
+                .about("List cached archives")
+            )
+            .subcommand(Command::new("clean")
+                .about("Remove all cached archives")
+            )
+            .subcommand(Command::new("size")
+                .about("Show the total size of the cache")
+            )
         )
         .subcommand(Command::new("create")
             .about("Create a Nodos plugin or subsystem module")
@@ -261,6 +533,28 @@ fn main() {
                 .required(false)
                 .long("clean-modules")
             )
+            .arg(Arg::new("platform")
+                .help("Platform of the Nodos release to bring, e.g. x86_64-windows. Defaults to the current platform.\n\
+                Modules in a release for another platform are not registered to the workspace and the running nosman is not replaced.")
+                .long("platform")
+                .required(false)
+            )
+        )
+        .subcommand(Command::new("keygen")
+            .about("Generate an ed25519 key pair to sign published releases")
+            .after_help("The secret key is written to <output> and the public key to <output>.pub.\n\
+            Users of a remote can trust the public key with 'remote trust'.")
+            .arg(Arg::new("output")
+                .help("Path of the secret key file")
+                .required(true)
+            )
+            .arg(Arg::new("force")
+                .action(ArgAction::SetTrue)
+                .long("force")
+                .help("Overwrite the key files if they exist")
+                .num_args(0)
+                .required(false)
+            )
         )
         .subcommand(Command::new("publish")
             .about("Publish a package")
@@ -339,9 +633,10 @@ fn main() {
             )
             .arg(Arg::new("target_platform")
                 .long("target-platform")
-                .help("Target architecture and operating system of the module to be published. If not provided, the current platform will be used.")
+                .help("Target platform of the module to be published, e.g. x86_64-windows, x86_64-linux-musl, or 'any' for packages without binaries. If not provided, the current platform will be used.")
                 .required(false)
             )
+            .arg(signing_key_arg.clone())
         )
         .subcommand(Command::new("publish-batch")
             .about("Publish all/changed modules under the git repository.")
@@ -407,9 +702,10 @@ fn main() {
             )
             .arg(Arg::new("target_platform")
                 .long("target-platform")
-                .help("Target architecture and operating system of the module to be published. If not provided, the current platform will be used.")
+                .help("Target platform of the module to be published, e.g. x86_64-windows, x86_64-linux-musl, or 'any' for packages without binaries. If not provided, the current platform will be used.")
                 .required(false)
             )
+            .arg(signing_key_arg.clone())
         )
         .subcommand(Command::new("unpublish")
             .alias("yank")
@@ -549,6 +845,13 @@ fn main() {
     let matches = cmd.get_matches();
 
     let workspace_dir = std::path::PathBuf::from(matches.get_one::<String>("workspace").unwrap());
+    nosman::common::set_offline(*matches.get_one::<bool>("offline").unwrap() || nosman::common::is_offline_env_set());
+    let timeout = *matches.get_one::<u64>("timeout").unwrap();
+    nosman::common::set_network_options(nosman::common::NetworkOptions {
+        connect_timeout: std::time::Duration::from_secs(*matches.get_one::<u64>("connect_timeout").unwrap()),
+        timeout: if timeout == 0 { None } else { Some(std::time::Duration::from_secs(timeout)) },
+        retries: *matches.get_one::<u32>("retries").unwrap(),
+    });
 
     // If contains --silently-agree-eula, agree to EULAs
     if let Some(agree_eula) = matches.get_one::<bool>("silently_agree_eula") {
@@ -4,6 +4,13 @@ mod index;
 pub(crate) mod workspace;
 pub(crate) mod path;
 pub mod constants;
-mod common;
+pub(crate) mod common;
 pub(crate) mod eula;
 mod platform;
+mod lockfile;
+mod workspace_manifest;
+mod signing;
+mod cache;
+mod archive;
+mod git;
+pub(crate) mod version_req;
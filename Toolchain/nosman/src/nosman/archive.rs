@@ -0,0 +1,342 @@
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use zip::ZipArchive;
+use crate::nosman::command::CommandError;
+
+// Entries are never written outside the target directory: paths with '..' or absolute paths are refused,
+// symlinks must point inside the target directory and nothing is written through a symlink that leads outside.
+
+fn refused(entry: &str, reason: &str) -> CommandError {
+    CommandError::GenericError { message: format!("Refusing to extract archive entry '{}': {}", entry, reason) }
+}
+
+// Converts the name of an archive entry to a relative path, or tells why it is unsafe
+fn sanitize_entry_path(name: &str) -> Result<PathBuf, String> {
+    // It might contain \, so convert this to POSIX compatible path
+    let name = name.replace("\\", "/");
+    if name.starts_with('/') || (name.len() >= 2 && name.as_bytes()[1] == b':') {
+        return Err("absolute path".to_string());
+    }
+    let mut path = PathBuf::new();
+    for component in Path::new(&name).components() {
+        match component {
+            Component::Normal(c) => path.push(c),
+            Component::CurDir => {}
+            Component::ParentDir => return Err("path contains '..'".to_string()),
+            Component::RootDir | Component::Prefix(_) => return Err("absolute path".to_string()),
+        }
+    }
+    Ok(path)
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).map(|m| m.file_type().is_symlink()).unwrap_or(false)
+}
+
+// Whether a symlink at entry_path (relative to the target directory) pointing to link_target stays inside the target directory.
+// The path is resolved without following symlinks, so a link that would pass through an already extracted symlink
+// (e.g. 'a -> d/d/../..' with 'd -> .') is refused.
+fn is_link_target_inside(target: &Path, entry_path: &Path, link_target: &Path) -> bool {
+    let mut current = PathBuf::new();
+    if let Some(parent) = entry_path.parent() {
+        for component in parent.components() {
+            match component {
+                Component::Normal(c) => current.push(c),
+                Component::CurDir => continue,
+                _ => return false,
+            }
+            if is_symlink(&target.join(&current)) {
+                return false;
+            }
+        }
+    }
+    let components: Vec<Component> = link_target.components().collect();
+    for (i, component) in components.iter().enumerate() {
+        match component {
+            Component::Normal(c) => {
+                current.push(c);
+                // The link itself may point to a symlink, which is already checked
+                if i + 1 < components.len() && is_symlink(&target.join(&current)) {
+                    return false;
+                }
+            }
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !current.pop() {
+                    return false;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+// Checks a created link once more by resolving it. Links to entries that are not extracted yet can not be resolved.
+fn check_created_link(target: &Path, path: &Path, entry: &str) -> Result<(), CommandError> {
+    if let Ok(resolved) = dunce::canonicalize(path) {
+        if !resolved.starts_with(target) {
+            let _ = fs::remove_file(path);
+            return Err(refused(entry, "symlink points outside of the target directory"));
+        }
+    }
+    Ok(())
+}
+
+// Creates the directory and makes sure it does not lead outside of target (e.g. through a symlink)
+fn prepare_dir(target: &Path, dir: &Path, entry: &str) -> Result<(), CommandError> {
+    fs::create_dir_all(dir)?;
+    if !dunce::canonicalize(dir)?.starts_with(target) {
+        return Err(refused(entry, "it would be written outside of the target directory through a symlink"));
+    }
+    Ok(())
+}
+
+// Entries at the root of the archive have nothing to prepare
+fn prepare_parent(target: &Path, rel_path: &Path, entry: &str) -> Result<(), CommandError> {
+    match rel_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => prepare_dir(target, &target.join(parent), entry),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(link_target: &Path, path: &Path) -> Result<(), std::io::Error> {
+    std::os::unix::fs::symlink(link_target, path)
+}
+
+#[cfg(windows)]
+fn create_symlink(link_target: &Path, path: &Path) -> Result<(), std::io::Error> {
+    std::os::windows::fs::symlink_file(link_target, path)
+}
+
+fn is_zip_symlink(file: &zip::read::ZipFile) -> bool {
+    file.unix_mode().map(|mode| mode & 0o170000 == 0o120000).unwrap_or(false)
+}
+
+pub fn extract_zip(archive_file: File, target: &PathBuf) -> Result<(), CommandError> {
+    let mut archive = ZipArchive::new(archive_file)?;
+    fs::create_dir_all(target)?;
+    let target = dunce::canonicalize(target)?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().to_string();
+        let rel_path = sanitize_entry_path(&name).map_err(|reason| refused(&name, &reason))?;
+        // The root of the archive, e.g. './'
+        if rel_path.as_os_str().is_empty() {
+            continue;
+        }
+        let outpath = target.join(&rel_path);
+
+        if file.is_dir() {
+            prepare_dir(&target, &outpath, &name)?;
+        } else if is_zip_symlink(&file) {
+            let mut link_target = String::new();
+            file.read_to_string(&mut link_target)?;
+            let link_target = PathBuf::from(link_target.replace("\\", "/"));
+            prepare_parent(&target, &rel_path, &name)?;
+            if !is_link_target_inside(&target, &rel_path, &link_target) {
+                return Err(refused(&name, format!("symlink points outside of the target directory ({})", link_target.display()).as_str()));
+            }
+            create_symlink(&link_target, &outpath)?;
+            check_created_link(&target, &outpath, &name)?;
+        } else {
+            prepare_parent(&target, &rel_path, &name)?;
+            let mut outfile = File::create(&outpath)?;
+            std::io::copy(&mut file, &mut outfile)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn extract_tar<R: Read>(reader: R, target: &PathBuf) -> Result<(), CommandError> {
+    let mut archive = tar::Archive::new(reader);
+    fs::create_dir_all(target)?;
+    let target = dunce::canonicalize(target)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.display().to_string();
+        let rel_path = sanitize_entry_path(&name).map_err(|reason| refused(&name, &reason))?;
+        // The root of the archive, e.g. './' in archives created with 'tar czf x.tar.gz -C dir .'
+        if rel_path.as_os_str().is_empty() {
+            continue;
+        }
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let link_target = entry.link_name()?;
+            if link_target.is_none() {
+                return Err(refused(&name, "link without a target"));
+            }
+            let link_target = link_target.unwrap().to_path_buf();
+            prepare_parent(&target, &rel_path, &name)?;
+            // Hard link targets are relative to the archive root, symlink targets to the link itself
+            let inside = if entry_type.is_hard_link() {
+                sanitize_entry_path(&link_target.display().to_string()).is_ok()
+            } else {
+                is_link_target_inside(&target, &rel_path, &link_target)
+            };
+            if !inside {
+                return Err(refused(&name, format!("link points outside of the target directory ({})", link_target.display()).as_str()));
+            }
+        }
+        prepare_parent(&target, &rel_path, &name)?;
+        // unpack_in refuses to write through symlinks leading outside the target, but it does not check symlink targets
+        if !entry.unpack_in(&target)? {
+            return Err(refused(&name, "it would be written outside of the target directory"));
+        }
+        if entry_type.is_symlink() {
+            check_created_link(&target, &target.join(&rel_path), &name)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+    TarXz,
+    TarZst,
+}
+
+pub fn detect_format(header: &[u8]) -> Option<ArchiveFormat> {
+    if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+        Some(ArchiveFormat::Zip)
+    } else if header.starts_with(&[0x1f, 0x8b]) {
+        Some(ArchiveFormat::TarGz)
+    } else if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        Some(ArchiveFormat::TarXz)
+    } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Some(ArchiveFormat::TarZst)
+    } else {
+        None
+    }
+}
+
+// Chooses the extractor from the first bytes of the archive
+pub fn extract(mut archive_file: File, url: &str, target: &PathBuf) -> Result<(), CommandError> {
+    let mut header = Vec::new();
+    (&mut archive_file).take(8).read_to_end(&mut header)?;
+    archive_file.seek(SeekFrom::Start(0))?;
+    match detect_format(&header) {
+        Some(ArchiveFormat::Zip) => extract_zip(archive_file, target),
+        Some(ArchiveFormat::TarGz) => extract_tar(flate2::read::GzDecoder::new(archive_file), target),
+        Some(ArchiveFormat::TarXz) => extract_tar(xz2::read::XzDecoder::new(archive_file), target),
+        Some(ArchiveFormat::TarZst) => extract_tar(zstd::stream::read::Decoder::new(archive_file)?, target),
+        None => Err(CommandError::GenericError { message: format!("Unknown archive format: {}. Supported formats are zip, tar.gz, tar.xz and tar.zst.", url) }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_entry_path_accepts_relative_paths() {
+        assert_eq!(sanitize_entry_path("a/b/c.txt"), Ok(PathBuf::from("a/b/c.txt")));
+        assert_eq!(sanitize_entry_path("./a/./b"), Ok(PathBuf::from("a/b")));
+        assert_eq!(sanitize_entry_path("a\\b\\c.txt"), Ok(PathBuf::from("a/b/c.txt")));
+    }
+
+    #[test]
+    fn sanitize_entry_path_refuses_escaping_paths() {
+        assert!(sanitize_entry_path("../a").is_err());
+        assert!(sanitize_entry_path("a/../../b").is_err());
+        assert!(sanitize_entry_path("a\\..\\..\\b").is_err());
+        assert!(sanitize_entry_path("/etc/passwd").is_err());
+        assert!(sanitize_entry_path("\\etc\\passwd").is_err());
+        assert!(sanitize_entry_path("C:/Windows").is_err());
+    }
+
+    #[test]
+    fn link_target_inside_without_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dunce::canonicalize(dir.path()).unwrap();
+        assert!(is_link_target_inside(&target, Path::new("a/link"), Path::new("b")));
+        assert!(is_link_target_inside(&target, Path::new("a/link"), Path::new("../b")));
+        assert!(is_link_target_inside(&target, Path::new("a/b/link"), Path::new("./../../c")));
+        assert!(!is_link_target_inside(&target, Path::new("link"), Path::new("..")));
+        assert!(!is_link_target_inside(&target, Path::new("a/link"), Path::new("../../b")));
+        assert!(!is_link_target_inside(&target, Path::new("a/link"), Path::new("b/../../../c")));
+        assert!(!is_link_target_inside(&target, Path::new("link"), Path::new("/etc/passwd")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn link_target_through_existing_symlink_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dunce::canonicalize(dir.path()).unwrap();
+        create_symlink(Path::new("."), &target.join("d")).unwrap();
+        // Pointing to the symlink itself is fine, passing through it is not
+        assert!(is_link_target_inside(&target, Path::new("b"), Path::new("d")));
+        assert!(!is_link_target_inside(&target, Path::new("a"), Path::new("d/d/../..")));
+        // Neither is placing a link under it
+        assert!(!is_link_target_inside(&target, Path::new("d/a"), Path::new("x")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_tar_refuses_chained_symlinks() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, link) in [("d", "."), ("a", "d/d/../..")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            header.set_link_name(link).unwrap();
+            header.set_cksum();
+            builder.append_data(&mut header, name, std::io::empty()).unwrap();
+        }
+        let data = builder.into_inner().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("out");
+        assert!(extract_tar(data.as_slice(), &target).is_err());
+        assert!(!is_symlink(&target.join("a")));
+    }
+
+    #[test]
+    fn detect_format_from_magic_bytes() {
+        let cases: [(&[u8], Option<ArchiveFormat>); 9] = [
+            (b"PK\x03\x04\x14\x00\x00\x00", Some(ArchiveFormat::Zip)),
+            (b"PK\x05\x06\x00\x00\x00\x00", Some(ArchiveFormat::Zip)),
+            (&[0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00], Some(ArchiveFormat::TarGz)),
+            (&[0xfd, b'7', b'z', b'X', b'Z', 0x00, 0x00, 0x04], Some(ArchiveFormat::TarXz)),
+            (&[0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x58, 0x00, 0x00], Some(ArchiveFormat::TarZst)),
+            (b"PK", None),
+            (&[0xfd, b'7', b'z'], None),
+            (&[], None),
+            (b"ustar\x0000", None),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(detect_format(header), expected, "header {:02x?}", header);
+        }
+    }
+
+    // Appends an entry with the name as it is, tar::Builder would normalize './'
+    fn append_raw(builder: &mut tar::Builder<Vec<u8>>, name: &str, entry_type: tar::EntryType, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_mode(if entry_type.is_dir() { 0o755 } else { 0o644 });
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    #[test]
+    fn extract_tar_skips_root_entry() {
+        // As created by 'tar czf x.tar.gz -C dir .'
+        let mut builder = tar::Builder::new(Vec::new());
+        append_raw(&mut builder, "./", tar::EntryType::Directory, &[]);
+        append_raw(&mut builder, "./module/", tar::EntryType::Directory, &[]);
+        append_raw(&mut builder, "./module/a.txt", tar::EntryType::Regular, b"a");
+        append_raw(&mut builder, "./b.txt", tar::EntryType::Regular, b"b");
+        let data = builder.into_inner().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("out");
+        extract_tar(data.as_slice(), &target).unwrap();
+        assert_eq!(fs::read_to_string(target.join("module").join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(target.join("b.txt")).unwrap(), "b");
+    }
+}
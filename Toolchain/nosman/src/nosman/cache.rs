@@ -0,0 +1,151 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Seek, Write};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::nosman::command::CommandError;
+use crate::nosman::common::sha256_of;
+use crate::nosman::path::get_cache_dir;
+
+// Downloaded archives are stored under <cache>/archives/<sha256>.
+// For each URL, an entry under <cache>/entries/<sha256 of url>.json records the digest of the archive it served.
+// Everything is written to a temporary file first and then renamed, so that concurrent nosman processes can share the cache.
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CacheEntry {
+    pub url: String,
+    pub sha256: String,
+    pub size: u64,
+    pub date: String,
+}
+
+fn get_archives_dir() -> PathBuf {
+    get_cache_dir().join("archives")
+}
+
+fn get_entries_dir() -> PathBuf {
+    get_cache_dir().join("entries")
+}
+
+fn get_archive_path(sha256: &str) -> PathBuf {
+    get_archives_dir().join(sha256.to_lowercase())
+}
+
+fn get_entry_path(url: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    get_entries_dir().join(format!("{:x}.json", hasher.finalize()))
+}
+
+fn find_entry(url: &str) -> Option<CacheEntry> {
+    let content = fs::read_to_string(get_entry_path(url)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_entry(entry: &CacheEntry) -> Result<(), io::Error> {
+    let entries_dir = get_entries_dir();
+    fs::create_dir_all(&entries_dir)?;
+    let mut tmp = tempfile::NamedTempFile::new_in(&entries_dir)?;
+    serde_json::to_writer_pretty(&mut tmp, entry)?;
+    tmp.flush()?;
+    tmp.persist(get_entry_path(&entry.url)).map_err(|e| e.error)?;
+    Ok(())
+}
+
+// Opens the cached archive of url, if there is one. If expected_sha256 is provided, the archive with that digest is used regardless of the URL.
+// Returns the file (positioned at start) and its digest. Corrupted archives are removed from the cache.
+fn open_cached(url: &str, expected_sha256: Option<&String>) -> Option<(File, String)> {
+    let sha256 = match expected_sha256 {
+        Some(sha256) => sha256.to_lowercase(),
+        None => find_entry(url)?.sha256,
+    };
+    let path = get_archive_path(&sha256);
+    let mut file = File::open(&path).ok()?;
+    let digest = sha256_of(&mut file).ok()?;
+    if digest != sha256 {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    file.seek(io::SeekFrom::Start(0)).ok()?;
+    Some((file, digest))
+}
+
+// Returns the archive at url from the cache, or downloads it into the cache.
+// Returns the file (positioned at start) and the SHA-256 digest of its contents.
+// The archive is not added to the cache if its digest does not match expected_sha256, so that the caller can report the mismatch.
+pub fn get_or_download(url: &str, expected_sha256: Option<&String>, download: impl FnOnce(&mut File) -> Result<(), CommandError>) -> Result<(File, String), CommandError> {
+    if let Some(cached) = open_cached(url, expected_sha256) {
+        println!("Using cached archive of {}", url);
+        return Ok(cached);
+    }
+    let archives_dir = get_archives_dir();
+    fs::create_dir_all(&archives_dir).map_err(|e| CommandError::IOError { file: archives_dir.display().to_string(), message: format!("{}", e) })?;
+    let mut tmp = tempfile::NamedTempFile::new_in(&archives_dir)?;
+    download(tmp.as_file_mut())?;
+    tmp.as_file_mut().seek(io::SeekFrom::Start(0))?;
+    let digest = sha256_of(tmp.as_file_mut())?;
+    tmp.as_file_mut().seek(io::SeekFrom::Start(0))?;
+    if let Some(expected) = expected_sha256 {
+        if !expected.eq_ignore_ascii_case(&digest) {
+            return Ok((tmp.into_file(), digest));
+        }
+    }
+    let size = tmp.as_file().metadata()?.len();
+    let file = tmp.persist(get_archive_path(&digest)).map_err(|e| e.error)?;
+    write_entry(&CacheEntry {
+        url: url.to_string(),
+        sha256: digest.clone(),
+        size,
+        date: chrono::Utc::now().to_rfc3339(),
+    })?;
+    Ok((file, digest))
+}
+
+// Entries whose archives are missing are skipped
+pub fn list_entries() -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    let dir = fs::read_dir(get_entries_dir());
+    if dir.is_err() {
+        return entries;
+    }
+    for dir_entry in dir.unwrap().flatten() {
+        let content = fs::read_to_string(dir_entry.path());
+        if content.is_err() {
+            continue;
+        }
+        let entry: Result<CacheEntry, _> = serde_json::from_str(&content.unwrap());
+        if let Ok(entry) = entry {
+            if get_archive_path(&entry.sha256).exists() {
+                entries.push(entry);
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.url.cmp(&b.url));
+    entries
+}
+
+// Total size of the archives in the cache, in bytes
+pub fn get_size() -> u64 {
+    let dir = fs::read_dir(get_archives_dir());
+    if dir.is_err() {
+        return 0;
+    }
+    dir.unwrap().flatten()
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+// Removes everything in the cache. Returns the number of bytes freed.
+pub fn clean() -> Result<u64, io::Error> {
+    let size = get_size();
+    for dir in [get_archives_dir(), get_entries_dir()] {
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+    }
+    Ok(size)
+}
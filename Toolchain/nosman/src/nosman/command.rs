@@ -17,6 +17,17 @@ mod pin;
 mod node;
 mod dev;
 pub(crate) mod launch;
+mod sync;
+mod keygen;
+mod cache;
+mod outdated;
+mod upgrade;
+mod search;
+mod releases;
+mod autoremove;
+mod mark;
+mod tree;
+mod check;
 
 use std::io;
 
@@ -56,6 +67,7 @@ pub fn commands() -> Vec<Box<dyn Command>> {
         Box::new(init::InitCommand {}),
         Box::new(remote::RemoteAddCommand {}),
         Box::new(remote::RemoteListCommand {}),
+        Box::new(remote::RemoteTrustCommand {}),
         Box::new(install::InstallCommand {}),
         Box::new(info::InfoCommand {}),
         Box::new(remove::RemoveCommand {}),
@@ -74,5 +86,19 @@ pub fn commands() -> Vec<Box<dyn Command>> {
         Box::new(dev::DevPullCommand {}),
         Box::new(dev::DevGenCommand {}),
         Box::new(launch::LaunchCommand {}),
+        Box::new(sync::SyncCommand {}),
+        Box::new(keygen::KeygenCommand {}),
+        Box::new(cache::CacheListCommand {}),
+        Box::new(cache::CacheCleanCommand {}),
+        Box::new(cache::CacheSizeCommand {}),
+        Box::new(outdated::OutdatedCommand {}),
+        Box::new(upgrade::UpgradeCommand {}),
+        Box::new(search::SearchCommand {}),
+        Box::new(releases::ReleasesCommand {}),
+        Box::new(autoremove::AutoremoveCommand {}),
+        Box::new(mark::MarkCommand {}),
+        Box::new(tree::TreeCommand {}),
+        Box::new(tree::WhyCommand {}),
+        Box::new(check::CheckCommand {}),
     ]
 }
@@ -0,0 +1,38 @@
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::workspace::Workspace;
+
+pub struct AutoremoveCommand {
+}
+
+impl AutoremoveCommand {
+    fn run_autoremove(&self, dry_run: bool) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        let orphans = workspace.get_orphaned_modules();
+        if orphans.is_empty() {
+            println!("{}", "No modules to remove".green());
+            return Ok(true);
+        }
+        println!("{}", format!("{} module(s) are no longer required by any explicitly installed module:", orphans.len()).yellow());
+        for id in &orphans {
+            println!("  {}", id);
+        }
+        if dry_run {
+            return Ok(true);
+        }
+        workspace.remove_modules(&orphans)
+    }
+}
+
+impl Command for AutoremoveCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("autoremove")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let dry_run = args.get_one::<bool>("dry_run").unwrap();
+        self.run_autoremove(*dry_run)
+    }
+}
@@ -0,0 +1,97 @@
+use clap::{ArgMatches};
+use colored::Colorize;
+use indicatif::HumanBytes;
+
+use crate::nosman::cache;
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::path::get_cache_dir;
+
+pub struct CacheListCommand {
+}
+
+impl CacheListCommand {
+    fn run_list(&self) -> CommandResult {
+        let entries = cache::list_entries();
+        if entries.is_empty() {
+            println!("Download cache at {} is empty", get_cache_dir().display());
+            return Ok(true);
+        }
+        println!("{}", format!("Download cache at {}", get_cache_dir().display()).green());
+        for entry in &entries {
+            println!("  {} {}", entry.url, format!("({}, sha256: {}, {})", HumanBytes(entry.size), entry.sha256, entry.date).dimmed());
+        }
+        Ok(true)
+    }
+}
+
+impl Command for CacheListCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        if let Some(subcommand) = args.subcommand_matches("cache") {
+            return subcommand.subcommand_matches("list");
+        }
+        None
+    }
+
+    fn run(&self, _args: &ArgMatches) -> CommandResult {
+        self.run_list()
+    }
+
+    fn needs_workspace(&self) -> bool {
+        false
+    }
+}
+
+pub struct CacheCleanCommand {
+}
+
+impl CacheCleanCommand {
+    fn run_clean(&self) -> CommandResult {
+        let freed = cache::clean()?;
+        println!("{}", format!("Removed {} from download cache at {}", HumanBytes(freed), get_cache_dir().display()).green());
+        Ok(true)
+    }
+}
+
+impl Command for CacheCleanCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        if let Some(subcommand) = args.subcommand_matches("cache") {
+            return subcommand.subcommand_matches("clean");
+        }
+        None
+    }
+
+    fn run(&self, _args: &ArgMatches) -> CommandResult {
+        self.run_clean()
+    }
+
+    fn needs_workspace(&self) -> bool {
+        false
+    }
+}
+
+pub struct CacheSizeCommand {
+}
+
+impl CacheSizeCommand {
+    fn run_size(&self) -> CommandResult {
+        println!("{} ({})", HumanBytes(cache::get_size()), get_cache_dir().display());
+        Ok(true)
+    }
+}
+
+impl Command for CacheSizeCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        if let Some(subcommand) = args.subcommand_matches("cache") {
+            return subcommand.subcommand_matches("size");
+        }
+        None
+    }
+
+    fn run(&self, _args: &ArgMatches) -> CommandResult {
+        self.run_size()
+    }
+
+    fn needs_workspace(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,75 @@
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::GenericError;
+use crate::nosman::version_req::VersionReq;
+use crate::nosman::workspace::Workspace;
+
+pub struct CheckCommand {
+}
+
+impl CheckCommand {
+    // Dependencies of installed modules that have no matching installed version
+    fn find_unresolved_dependencies(workspace: &Workspace) -> Vec<String> {
+        let mut problems = Vec::new();
+        for versions in workspace.installed_modules.values() {
+            for module in versions.values() {
+                for dep in module.info.dependencies.clone().unwrap_or_default() {
+                    let req = VersionReq::parse(&dep.version);
+                    if let Err(e) = req {
+                        problems.push(format!("{} has an invalid version requirement for {}: {}", module.info.id, dep.name, e));
+                        continue;
+                    }
+                    if workspace.get_latest_installed_module_matching(&dep.name, &req.unwrap()).is_some() {
+                        continue;
+                    }
+                    let installed: Vec<String> = workspace.get_installed_modules(&dep.name).iter().map(|m| m.info.id.version.clone()).collect();
+                    if installed.is_empty() {
+                        problems.push(format!("{} requires {} {}, which is not installed", module.info.id, dep.name, dep.version));
+                    } else {
+                        problems.push(format!("{} requires {} {}, but only {} is installed", module.info.id, dep.name, dep.version, installed.join(", ")));
+                    }
+                }
+            }
+        }
+        problems.sort();
+        problems
+    }
+
+    fn run_check(&self) -> CommandResult {
+        let workspace = Workspace::get()?;
+        let conflicts = workspace.find_dependency_conflicts(&vec![]);
+        let unresolved = Self::find_unresolved_dependencies(&workspace);
+        if conflicts.is_empty() && unresolved.is_empty() {
+            println!("{}", "No dependency problems found".green());
+            return Ok(true);
+        }
+        if !conflicts.is_empty() {
+            println!("{}", format!("{} dependency conflict(s):", conflicts.len()).red());
+            for conflict in &conflicts {
+                println!("  {}", conflict.dependency.bold());
+                for (id, req) in &conflict.requirements {
+                    println!("      {} requires {}", id, req);
+                }
+            }
+        }
+        if !unresolved.is_empty() {
+            println!("{}", format!("{} unresolved dependency(ies):", unresolved.len()).red());
+            for problem in &unresolved {
+                println!("  {}", problem);
+            }
+        }
+        Err(GenericError { message: format!("Found {} dependency conflict(s) and {} unresolved dependency(ies)", conflicts.len(), unresolved.len()) })
+    }
+}
+
+impl Command for CheckCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("check")
+    }
+
+    fn run(&self, _args: &ArgMatches) -> CommandResult {
+        self.run_check()
+    }
+}
@@ -7,12 +7,14 @@ use indicatif::ProgressBar;
 use rayon::prelude::*;
 use CommandError::InvalidArgumentError;
 use crate::nosman::command::{Command, CommandError, CommandResult};
+use crate::nosman::common::check_online;
 
 pub struct DevPullCommand {
 }
 
 impl DevPullCommand {
     fn run_pull(&self, dirs: Vec<PathBuf>) -> Result<bool, CommandError> {
+        check_online("Pulling git repositories")?;
         // Scan module folder for git repositories and run "git pull" on them
         let pb = ProgressBar::new_spinner();
         pb.enable_steady_tick(Duration::from_millis(100));
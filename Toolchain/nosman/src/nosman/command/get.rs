@@ -12,16 +12,19 @@ use linked_hash_set::LinkedHashSet;
 use crate::nosman::command::{Command, CommandError, CommandResult};
 use crate::nosman::command::CommandError::{InvalidArgumentError, IOError};
 use crate::nosman::command::init::InitCommand;
-use crate::nosman::index::{PackageType, SemVer};
+use crate::nosman::index::PackageType;
 use crate::nosman::common::{download_and_extract};
-use crate::nosman::{common, workspace};
+use crate::nosman::{common, constants, workspace};
+use crate::nosman::signing::SignatureCheck;
+use crate::nosman::platform::{get_host_platform, parse_platform_or_host, Platform};
+use crate::nosman::version_req::VersionReq;
 use crate::nosman::workspace::Workspace;
 
 pub struct GetCommand {
 }
 
 impl GetCommand {
-    fn move_file_or_dir(src: &PathBuf, dst: &PathBuf) -> Result<(), io::Error> {
+    pub(crate) fn move_file_or_dir(src: &PathBuf, dst: &PathBuf) -> Result<(), io::Error> {
         if src.is_dir() {
             let opts = fs_more::directory::DirectoryMoveOptions {
                 destination_directory_rule: fs_more::directory::DestinationDirectoryRule::AllowNonEmpty {
@@ -133,7 +136,7 @@ impl GetCommand {
         }
         Ok(())
     }
-    fn run_get(&self, path: &PathBuf, nodos_name: &String, version: Option<&String>, fetch_index: bool, dont_ask: bool, clean_modules: bool) -> CommandResult {
+    pub(crate) fn run_get(&self, path: &PathBuf, nodos_name: &String, version: Option<&String>, fetch_index: bool, dont_ask: bool, clean_modules: bool, platform: &Platform) -> CommandResult {
         // If not under a workspace, init
         if !workspace::exists_in(path) {
             println!("No workspace found, initializing one under {:?}", path);
@@ -154,28 +157,25 @@ impl GetCommand {
             pb.finish_and_clear();
             workspace.fetch_package_releases(nodos_name);
             workspace.save()?;
-            return self.run_get(path, nodos_name, version, false, dont_ask, clean_modules)
+            return self.run_get(path, nodos_name, version, false, dont_ask, clean_modules, platform)
         }
 
         let res;
         if let Some(version) = version {
-            let version_start = SemVer::parse_from_string(version).expect(format!("Invalid semantic version: {}", version).as_str());
-            if version_start.minor.is_none() {
-                return Err(InvalidArgumentError { message: "Please provide a minor version too!".to_string() });
-            }
-            let version_end = version_start.get_one_up();
-            res = workspace.index_cache.get_latest_compatible_release_within_range(nodos_name, &version_start, &version_end);
+            let req = VersionReq::parse(version).map_err(|message| InvalidArgumentError { message })?;
+            res = workspace.index_cache.get_latest_release_matching(nodos_name, |r| r.is_for_platform(platform) && req.matches_str(&r.version));
         }
         else {
-            res = workspace.index_cache.get_latest_release(nodos_name);
+            res = workspace.index_cache.get_latest_release(nodos_name, platform);
         }
         if res.is_none() {
             return if version.is_none() {
-                Err(InvalidArgumentError { message: format!("No release found for {}", nodos_name) })
+                Err(InvalidArgumentError { message: format!("No release found for {} for platform {}", nodos_name, platform) })
             } else {
-                Err(InvalidArgumentError { message: format!("No release found for {} version {}", nodos_name, version.unwrap()) })
+                Err(InvalidArgumentError { message: format!("No release found for {} version {} for platform {}", nodos_name, version.unwrap(), platform) })
             }
         }
+        let is_host_platform = *platform == get_host_platform();
         let (package_type, release) = res.unwrap();
         if *package_type != PackageType::Nodos {
             return Err(InvalidArgumentError { message: format!("Package {} found in the index is not a Nodos package", nodos_name) });
@@ -183,7 +183,8 @@ impl GetCommand {
         let tmpdir = tempfile::tempdir()?;
         let downloaded_path = tmpdir.path().to_path_buf();
         pb.println(format!("Downloading and extracting {}-{}", nodos_name, release.version));
-        let res = download_and_extract(&release.url, &downloaded_path);
+        let signature_check = SignatureCheck::for_release(&workspace, nodos_name, release)?;
+        let res = download_and_extract(&release.url, &downloaded_path, release.sha256.as_ref(), signature_check.as_ref());
         if let Err(e) = res {
             return Err(e);
         }
@@ -276,6 +277,10 @@ impl GetCommand {
             }
             // If file is same as current executable, use self_replace
             if cur_dst_path == current_exe {
+                if !is_host_platform {
+                    pb.println(format!("Skip replacing the running nosman with the one for {}", platform).yellow().dimmed().to_string());
+                    continue;
+                }
                 replace_nosman_with = Some(curr_file_path.to_path_buf());
                 continue;
             }
@@ -330,6 +335,10 @@ impl GetCommand {
                 continue;
             }
             let relative_path = file.strip_prefix(&dst_path).unwrap();
+            // Workspace lockfile & manifest are not a part of Nodos releases
+            if relative_path == PathBuf::from(constants::LOCKFILE_NAME) || relative_path == PathBuf::from(constants::WORKSPACE_MANIFEST_FILE_NAME) {
+                continue;
+            }
             if !clean_modules && relative_path.starts_with("Module/") {
                 // TODO: Don't simply skip removing, remove the older one.
                 pb.println(format!("Skip deleting: {}", file.display()).yellow().dimmed().to_string());
@@ -350,7 +359,8 @@ impl GetCommand {
             }
         }
 
-        if !clean_modules {
+        // Modules of another platform are not registered to the workspace
+        if !clean_modules && is_host_platform {
             pb.println("Rescanning...");
             drop(pb);
             let _ = Workspace::new(&dst_path)?;
@@ -370,7 +380,8 @@ impl Command for GetCommand {
         let version = args.get_one::<String>("version");
         let dont_ask = args.get_one::<bool>("yes_to_all").unwrap();
         let clean_modules = args.get_one::<bool>("clean_modules").unwrap();
-        self.run_get(&workspace::current_root().unwrap(), nodos_name, version, true, *dont_ask, *clean_modules)
+        let platform = parse_platform_or_host(args.get_one::<String>("platform")).map_err(|message| InvalidArgumentError { message })?;
+        self.run_get(&workspace::current_root().unwrap(), nodos_name, version, true, *dont_ask, *clean_modules, &platform)
     }
 
     fn needs_workspace(&self) -> bool {
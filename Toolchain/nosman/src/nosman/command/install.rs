@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::PathBuf;
 
 use clap::{ArgMatches};
@@ -9,8 +11,20 @@ use crate::nosman::command::{Command, CommandError, CommandResult};
 use zip::result::ZipError;
 use nosman::workspace::Workspace;
 use crate::nosman::command::CommandError::{GenericError, InvalidArgumentError};
-use crate::nosman::index::{PackageType, SemVer};
+use crate::nosman::index::{PackageReleaseEntry, PackageType, SemVer};
 use crate::nosman::common::download_and_extract;
+use crate::nosman::module::{get_module_manifests, InstalledModule, PackageIdentifier};
+use crate::nosman::constants;
+use crate::nosman::archive;
+use crate::nosman::git;
+use crate::nosman::common::check_online;
+use crate::nosman::lockfile::{LockedModule, Lockfile};
+use crate::nosman::command::get::GetCommand;
+use crate::nosman::command::sdk_info::EngineApiVersions;
+use crate::nosman::platform::{get_host_platform, parse_platform_or_host, Platform};
+use crate::nosman::version_req::VersionReq;
+use crate::nosman::signing::SignatureCheck;
+use crate::nosman::workspace::get_nosman_dir_for;
 
 pub struct InstallCommand {
 }
@@ -21,45 +35,260 @@ impl From<ZipError> for CommandError {
     }
 }
 
+fn parse_version_req(version: &str) -> Result<VersionReq, CommandError> {
+    VersionReq::parse(version).map_err(|message| InvalidArgumentError { message })
+}
+
 impl InstallCommand {
-    pub(crate) fn run_install(&self, package_name: &str, version_opt: Option<&String>, exact: bool, output_dir: &PathBuf, prefix: Option<&String>) -> CommandResult {
+    pub(crate) fn run_install(&self, package_name: &str, version_opt: Option<&String>, exact: bool, output_dir: &PathBuf, prefix: Option<&String>, install_dependencies: bool, locked: bool, ignore_api_version: bool, include_pre: bool, platform: &Platform) -> CommandResult {
         // Fetch remotes
         let mut workspace = Workspace::get()?;
-        workspace.fetch_package_releases(package_name);
-        let version;
+        // Modules of another platform are only downloaded, e.g. to assemble a deployment bundle
+        let is_host_platform = *platform == get_host_platform();
+        if locked && !is_host_platform {
+            return Err(InvalidArgumentError { message: "--locked can not be used with a platform other than the host platform".to_string() });
+        }
+        let lockfile = if locked {
+            if !Lockfile::exists_in(&workspace.root) {
+                return Err(InvalidArgumentError { message: format!("--locked is set but there is no {} under {}", constants::LOCKFILE_NAME, workspace.root.display()) });
+            }
+            Some(Lockfile::load(&workspace.root)?)
+        } else {
+            workspace.fetch_package_releases(package_name);
+            None
+        };
+        // Locked installs use the versions in the lockfile as they are
+        let api_versions = if ignore_api_version || locked || !is_host_platform { None } else { EngineApiVersions::of_workspace() };
+        let mut version;
         if version_opt.is_none() {
-            let latest = workspace.index_cache.get_latest_release(package_name);
-            if latest.is_none() {
-                return Err(InvalidArgumentError { message: format!("No versions found for package {}", package_name) });
+            if let Some(lockfile) = &lockfile {
+                let latest = Self::get_latest_locked_version(lockfile, package_name, |_| true);
+                if latest.is_none() {
+                    return Err(InvalidArgumentError { message: format!("Package {} is not in the lockfile", package_name) });
+                }
+                version = latest.unwrap();
+            } else {
+                let latest = workspace.index_cache.get_latest_release_matching(package_name, |r| r.is_for_platform(platform)
+                    && (include_pre || !r.is_pre_release()) && Self::is_loadable(api_versions.as_ref(), r));
+                if latest.is_none() {
+                    if let (Some(api_versions), Some((_, newest))) = (&api_versions, workspace.index_cache.get_latest_release(package_name, platform)) {
+                        return Err(Self::api_version_error(api_versions, package_name, newest));
+                    }
+                    return Err(InvalidArgumentError { message: format!("No versions found for package {} for platform {}", package_name, platform) });
+                }
+                version = latest.unwrap().1.version.clone();
             }
-            version = latest.unwrap().1.version.clone();
         } else {
             version = version_opt.unwrap().to_string();
         }
         if !exact {
-            // Find or download a version such that 'a.b <= x < a.(b+1)'
-            let version_start = SemVer::parse_from_string(version.as_str()).unwrap();
-            if version_start.minor.is_none() {
-                return Err(InvalidArgumentError { message: "Please provide a minor version too!".to_string() });
-            }
-            let version_end = version_start.get_one_up();
-            return if let Some(installed_module) = workspace.get_latest_installed_module_within_range(package_name, &version_start, &version_end) {
+            // Find or download the latest version that matches the requirement
+            let req = parse_version_req(version.as_str())?.including_pre_releases(include_pre);
+            let installed_module = if is_host_platform { workspace.get_latest_installed_module_matching(package_name, &req) } else { None };
+            if let Some(installed_module) = installed_module {
+                Self::check_locked(lockfile.as_ref(), package_name, &installed_module.info.id.version)?;
                 println!("{}", format!("Found an already installed compatible version for {} version {}: {}", package_name, version, installed_module.info.id.version).as_str().yellow());
-                Ok(true)
+                version = installed_module.info.id.version.clone();
             } else {
-                if let Some((package_type, release)) = workspace.index_cache.get_latest_compatible_release_within_range(package_name, &version_start, &version_end) {
-                    if *package_type == PackageType::Nodos || *package_type == PackageType::Engine {
-                        return Err(InvalidArgumentError { message: format!("Package {} requires special treatment", package_name) });
+                version = Self::get_installable_version_matching(&workspace, lockfile.as_ref(), api_versions.as_ref(), package_name, &req, platform)?;
+            }
+        } else if let Some(stripped) = version.strip_prefix('=') {
+            version = stripped.trim().to_string();
+        }
+        let root = PackageIdentifier { name: package_name.to_string(), version: version.clone() };
+        if install_dependencies {
+            let missing = self.resolve_dependencies(&mut workspace, lockfile.as_ref(), api_versions.as_ref(), package_name, version.as_str(), platform)?;
+            if !missing.is_empty() {
+                println!("{}", format!("{}-{} requires {} missing dependencies: {}", package_name, version, missing.len(),
+                                       missing.iter().map(|d| d.to_string()).collect::<Vec<String>>().join(", ")).as_str().yellow());
+            }
+            if is_host_platform {
+                Self::warn_dependency_conflicts(&workspace, &root, &missing);
+            }
+            for dependency in &missing {
+                self.install_package(&mut workspace, lockfile.as_ref(), api_versions.as_ref(), dependency.name.as_str(), dependency.version.as_str(), output_dir, None, platform)?;
+                if is_host_platform {
+                    workspace.set_installed_as_dependency(&dependency.name, &dependency.version, true);
+                    workspace.save()?;
+                }
+            }
+        } else if is_host_platform {
+            Self::warn_dependency_conflicts(&workspace, &root, &vec![]);
+        }
+        self.install_package(&mut workspace, lockfile.as_ref(), api_versions.as_ref(), package_name, version.as_str(), output_dir, prefix, platform)?;
+        if !is_host_platform {
+            return Ok(true);
+        }
+        // A module that is asked for is kept by autoremove, even if it was installed as a dependency before
+        workspace.set_installed_as_dependency(package_name, version.as_str(), false);
+        workspace.save()?;
+        Ok(true)
+    }
+
+    // Explains the conflicts the package and its missing dependencies would introduce with the installed modules
+    fn warn_dependency_conflicts(workspace: &Workspace, package: &PackageIdentifier, missing: &Vec<PackageIdentifier>) {
+        let new_modules: Vec<(PackageIdentifier, Vec<PackageIdentifier>)> = std::iter::once(package).chain(missing.iter())
+            .map(|id| (id.clone(), Self::get_dependencies_of(workspace, &id.name, &id.version, &get_host_platform())))
+            .collect();
+        for conflict in workspace.find_dependency_conflicts(&new_modules) {
+            if !conflict.requirements.iter().any(|(id, _)| new_modules.iter().any(|(new_id, _)| new_id == id)) {
+                continue;
+            }
+            println!("{}", format!("Warning: {}. Modules requiring different versions of {} may fail to load together.", conflict, conflict.dependency).as_str().yellow());
+        }
+    }
+
+    fn is_loadable(api_versions: Option<&EngineApiVersions>, release: &PackageReleaseEntry) -> bool {
+        api_versions.map(|a| a.can_load(release)).unwrap_or(true)
+    }
+
+    fn api_version_error(api_versions: &EngineApiVersions, package_name: &str, release: &PackageReleaseEntry) -> CommandError {
+        let reason = api_versions.check(release).err().unwrap_or_default();
+        InvalidArgumentError { message: format!("{}-{} can not be loaded by the engines in the workspace: {}. Use --ignore-api-version to install it anyway.", package_name, release.version, reason) }
+    }
+
+    // Returns the latest version matching the requirement that can be installed.
+    // If a lockfile is provided, only the versions recorded in it are considered.
+    // Otherwise, releases that the engines in the workspace can not load are skipped.
+    fn get_installable_version_matching(workspace: &Workspace, lockfile: Option<&Lockfile>, api_versions: Option<&EngineApiVersions>, package_name: &str, req: &VersionReq, platform: &Platform) -> Result<String, CommandError> {
+        if let Some(lockfile) = lockfile {
+            return match Self::get_latest_locked_version(lockfile, package_name, |v| req.matches(v)) {
+                Some(v) => Ok(v),
+                None => Err(InvalidArgumentError { message: format!("The lockfile does not contain a version matching {} for module {}. Run install without --locked to update the lockfile.",
+                                                                    req, package_name) }),
+            };
+        }
+        let in_range = |r: &PackageReleaseEntry| r.is_for_platform(platform) && req.matches_str(&r.version);
+        if let Some((package_type, release)) = workspace.index_cache.get_latest_release_matching(package_name, |r| in_range(r) && Self::is_loadable(api_versions, r)) {
+            if *package_type == PackageType::Nodos || *package_type == PackageType::Engine {
+                return Err(InvalidArgumentError { message: format!("Package {} requires special treatment", package_name) });
+            }
+            return Ok(release.version.clone());
+        }
+        if let (Some(api_versions), Some((_, newest))) = (api_versions, workspace.index_cache.get_latest_release_matching(package_name, in_range)) {
+            return Err(Self::api_version_error(api_versions, package_name, newest));
+        }
+        Err(InvalidArgumentError { message: format!("No remote contained a version matching {} for module {} for platform {}", req, package_name, platform) })
+    }
+
+    // With --locked, an installed version can only be used if the lockfile contains it
+    fn check_locked(lockfile: Option<&Lockfile>, package_name: &str, version: &str) -> Result<(), CommandError> {
+        match lockfile {
+            Some(lockfile) if lockfile.find(package_name, version).is_none() =>
+                Err(InvalidArgumentError { message: format!("Module {} version {} is installed but it is not in the lockfile. Remove it or run install without --locked to update the lockfile.", package_name, version) }),
+            _ => Ok(()),
+        }
+    }
+
+    fn get_latest_locked_version<F: Fn(&SemVer) -> bool>(lockfile: &Lockfile, package_name: &str, filter: F) -> Option<String> {
+        let mut locked_versions: Vec<(SemVer, &String)> = lockfile.find_all(package_name).into_iter()
+            .filter_map(|m| SemVer::parse_from_string(&m.version).map(|v| (v, &m.version)))
+            .filter(|(v, _)| filter(v))
+            .collect();
+        locked_versions.sort_by(|a, b| a.0.cmp(&b.0));
+        locked_versions.last().map(|(_, version)| (*version).clone())
+    }
+
+    fn run_install_locked(&self, output_dir: &PathBuf) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        if !Lockfile::exists_in(&workspace.root) {
+            return Err(InvalidArgumentError { message: format!("--locked is set but there is no {} under {}", constants::LOCKFILE_NAME, workspace.root.display()) });
+        }
+        let lockfile = Lockfile::load(&workspace.root)?;
+        for locked in &lockfile.modules {
+            if locked.url.is_none() {
+                if workspace.get_installed_module(&locked.name, &locked.version).is_none() {
+                    println!("{}", format!("Skipping {}-{}: It was not installed from a remote", locked.name, locked.version).as_str().yellow());
+                }
+                continue;
+            }
+            self.install_package(&mut workspace, Some(&lockfile), None, &locked.name, &locked.version, output_dir, None, &get_host_platform())?;
+        }
+        println!("{}", format!("All modules in {} are installed", constants::LOCKFILE_NAME).as_str().green());
+        Ok(true)
+    }
+
+    fn get_dependencies_of(workspace: &Workspace, package_name: &str, version: &str, platform: &Platform) -> Vec<PackageIdentifier> {
+        // Installed modules are of the host platform
+        if *platform == get_host_platform() {
+            if let Some(module) = workspace.get_installed_module(package_name, version) {
+                return module.info.dependencies.clone().unwrap_or_default();
+            }
+        }
+        if let Some((_package_type, release)) = workspace.index_cache.get_package(package_name, version, platform) {
+            return release.dependencies.clone().unwrap_or_default();
+        }
+        vec![]
+    }
+
+    // Walks the dependency graph of the package and returns the dependencies that are not installed,
+    // ordered such that deeper dependencies come first.
+    fn resolve_dependencies(&self, workspace: &mut Workspace, lockfile: Option<&Lockfile>, api_versions: Option<&EngineApiVersions>, package_name: &str, version: &str, platform: &Platform) -> Result<Vec<PackageIdentifier>, CommandError> {
+        let is_host_platform = *platform == get_host_platform();
+        let root = PackageIdentifier { name: package_name.to_string(), version: version.to_string() };
+        let mut missing: Vec<PackageIdentifier> = Vec::new();
+        let mut visited: HashSet<PackageIdentifier> = HashSet::new();
+        let mut fetched: HashSet<String> = HashSet::new();
+        fetched.insert(package_name.to_string());
+        // (dependent, dependency)
+        let mut current: Vec<(PackageIdentifier, PackageIdentifier)> = Self::get_dependencies_of(workspace, package_name, version, platform)
+            .into_iter().map(|dep| (root.clone(), dep)).collect();
+        while !current.is_empty() {
+            let mut to_fetch: Vec<String> = Vec::new();
+            for (_dependent, dep) in &current {
+                if !fetched.contains(&dep.name) && !to_fetch.contains(&dep.name) {
+                    to_fetch.push(dep.name.clone());
+                }
+            }
+            if !to_fetch.is_empty() && lockfile.is_none() {
+                workspace.fetch_package_releases_of(&to_fetch);
+                fetched.extend(to_fetch);
+            }
+            let mut next = Vec::new();
+            for (dependent, dep) in current {
+                if !visited.insert(dep.clone()) {
+                    continue;
+                }
+                let res = VersionReq::parse(dep.version.as_str());
+                if let Err(e) = res {
+                    return Err(InvalidArgumentError { message: format!("Invalid version requirement for dependency {} of {}: {}", dep.name, dependent, e) });
+                }
+                let req = res.unwrap();
+                let resolved_version;
+                let installed_module = if is_host_platform { workspace.get_latest_installed_module_matching(&dep.name, &req) } else { None };
+                if let Some(installed_module) = installed_module {
+                    if let Err(e) = Self::check_locked(lockfile, &dep.name, &installed_module.info.id.version) {
+                        return Err(InvalidArgumentError { message: format!("Unable to resolve dependency {} of {}: {}", dep.name, dependent, e) });
                     }
-                    self.run_install(package_name, Some(&release.version), true, output_dir, prefix)
+                    resolved_version = installed_module.info.id.version.clone();
                 } else {
-                    Err(InvalidArgumentError { message: format!("No remote contained a version in range [{}, {}) for module {}", version_start.to_string(), version_end.to_string(), package_name) })
+                    let res = Self::get_installable_version_matching(workspace, lockfile, api_versions, &dep.name, &req, platform);
+                    if let Err(e) = res {
+                        return Err(InvalidArgumentError { message: format!("Unable to resolve dependency {} of {}: {}", dep.name, dependent, e) });
+                    }
+                    resolved_version = res.unwrap();
+                    let id = PackageIdentifier { name: dep.name.clone(), version: resolved_version.clone() };
+                    if !missing.contains(&id) {
+                        missing.push(id);
+                    }
+                }
+                let resolved = PackageIdentifier { name: dep.name.clone(), version: resolved_version };
+                for sub_dep in Self::get_dependencies_of(workspace, &resolved.name, &resolved.version, platform) {
+                    next.push((resolved.clone(), sub_dep));
                 }
             }
+            current = next;
         }
+        missing.reverse();
+        Ok(missing)
+    }
+
+    fn install_package(&self, workspace: &mut Workspace, lockfile: Option<&Lockfile>, api_versions: Option<&EngineApiVersions>, package_name: &str, version: &str, output_dir: &PathBuf, prefix: Option<&String>, platform: &Platform) -> CommandResult {
+        let is_host_platform = *platform == get_host_platform();
         let mut replace_entry_in_index = false;
-        if let Some(existing) = workspace.get_installed_module(package_name, version.as_str()) {
-            if existing.get_module_dir().exists() {
+        let existing = if is_host_platform { workspace.get_installed_module(package_name, version) } else { None };
+        if let Some(existing) = existing {
+            if workspace.root.join(existing.get_module_dir()).exists() {
                 println!("{}", format!("Module {} version {} is already installed", package_name, version).as_str().yellow());
                 return Ok(true);
             }
@@ -67,35 +296,261 @@ impl InstallCommand {
                 replace_entry_in_index = true;
             }
         }
-        if let Some((package_type, package)) = workspace.index_cache.get_package(package_name, version.as_str()) {
-            let mut install_dir = output_dir.clone();
-            if let Some(p) = prefix {
-                install_dir = install_dir.join(p);
-            } else if package_type.is_module() {
-                install_dir = install_dir.join(format!("{}-{}", package_name, version));
+        let mut source = if let Some(lockfile) = lockfile {
+            match lockfile.find(package_name, version) {
+                Some(locked) if locked.url.is_some() => locked.clone(),
+                Some(_) => return Err(InvalidArgumentError { message: format!("Module {} version {} was not installed from a remote, it can not be installed with --locked", package_name, version) }),
+                None => return Err(InvalidArgumentError { message: format!("Module {} version {} is not in the lockfile. Run install without --locked to update the lockfile.", package_name, version) }),
+            }
+        } else {
+            match workspace.index_cache.get_package(package_name, version, platform) {
+                Some((_package_type, release)) => {
+                    if let Some(api_versions) = api_versions {
+                        if !api_versions.can_load(release) {
+                            return Err(Self::api_version_error(api_versions, package_name, release));
+                        }
+                    }
+                    LockedModule::from_release(package_name, release)
+                }
+                None => return Err(GenericError { message: format!("None of the remotes contain package {} version {} for platform {}. You can try rescan command to update index.", package_name, version, platform) }),
             }
+        };
+        // Lockfile only contains modules
+        let package_type = workspace.index_cache.get_package(package_name, version, platform).map(|(t, _)| t.clone()).unwrap_or(PackageType::Plugin);
+
+        let mut install_dir = output_dir.clone();
+        if let Some(p) = prefix {
+            install_dir = install_dir.join(p);
+        } else if package_type.is_module() {
+            install_dir = install_dir.join(format!("{}-{}", package_name, version));
+        }
 
-            let pkg_type_str = if package_type.is_module() { "module" } else { "package" };
+        let pkg_type_str = if package_type.is_module() { "module" } else { "package" };
 
-            let final_out_dir = if install_dir.is_relative() && package_type.is_module() { workspace.root.join(install_dir) } else { install_dir };
-            let module_name_version = format!("{}-{}", package_name, version);
+        let final_out_dir = if install_dir.is_relative() && package_type.is_module() { workspace.root.join(install_dir) } else { install_dir };
+        let module_name_version = format!("{}-{}", package_name, version);
+        if !is_host_platform && final_out_dir.exists() {
+            println!("{}", format!("{} already exists, skipping {} {} for platform {}", final_out_dir.display(), pkg_type_str, module_name_version, platform).as_str().yellow());
+            return Ok(true);
+        }
+        if is_host_platform {
             println!("Downloading {} {}", pkg_type_str, module_name_version);
+        } else {
+            println!("Downloading {} {} for platform {}", pkg_type_str, module_name_version, platform);
+        }
 
-            let res = download_and_extract(&package.url, &final_out_dir);
-            if res.is_err() {
-                return Err(res.err().unwrap());
+        // Signatures are only in the index, so locked installs can only check them if the locked release is still there
+        let signature_check = match workspace.index_cache.get_package(package_name, version, platform).filter(|(_, release)| Some(&release.url) == source.url.as_ref()) {
+            Some((_, release)) => SignatureCheck::for_release(workspace, package_name, release)?,
+            None => {
+                SignatureCheck::check_unknown_release(workspace, source.url.as_ref().unwrap())?;
+                None
             }
+        };
+        if !package_type.is_module() {
+            download_and_extract(source.url.as_ref().unwrap(), &final_out_dir, source.sha256.as_ref(), signature_check.as_ref())?;
             println!("Extracted {} {} to {}", pkg_type_str, package_name, final_out_dir.display());
-            if package_type.is_module() {
-                workspace.scan_modules_in_folder(final_out_dir, replace_entry_in_index);
-                println!("Adding to workspace file");
-                workspace.save()?;
-            }
             println!("{}", format!("{}-{} installed successfully", package_name, version).as_str().green());
-            Ok(true)
+            return Ok(true);
+        }
+
+        // Modules are extracted into a staging directory first and moved into place only after they are validated.
+        // Staging directory is removed when it goes out of scope.
+        let staging_root = get_nosman_dir_for(&workspace.root).join("staging");
+        fs::create_dir_all(&staging_root)?;
+        let staging_dir = tempfile::Builder::new().prefix(&module_name_version).tempdir_in(&staging_root)?;
+        let extracted_dir = staging_dir.path().join("module");
+        let res = download_and_extract(source.url.as_ref().unwrap(), &extracted_dir, source.sha256.as_ref(), signature_check.as_ref());
+        if res.is_err() {
+            return Err(res.err().unwrap());
+        }
+        source.sha256 = Some(res.unwrap());
+        Self::validate_staged_module(&extracted_dir, package_name, version)?;
+        if !is_host_platform {
+            // Not registered to the workspace or the lockfile, the engines in the workspace can not load it
+            Self::move_into_place(&extracted_dir, &final_out_dir)?;
+            println!("{}", format!("{}-{} for platform {} downloaded to {}", package_name, version, platform, final_out_dir.display()).as_str().green());
+            return Ok(true);
+        }
+        Self::place_staged_module(workspace, &staging_dir.path().to_path_buf(), &extracted_dir, &final_out_dir, package_name, version, replace_entry_in_index, source)
+    }
+
+    // Moves a validated module from the staging directory to final_out_dir and registers it, rolling back on failure
+    fn place_staged_module(workspace: &mut Workspace, staging_dir: &PathBuf, staged_module_dir: &PathBuf, final_out_dir: &PathBuf, package_name: &str, version: &str, replace_entry_in_index: bool, source: LockedModule) -> CommandResult {
+        // An unregistered folder may already be there (e.g. from an interrupted install). Keep it until the new one is in place.
+        let previous_dir = staging_dir.join("previous");
+        let has_previous = final_out_dir.exists();
+        if has_previous {
+            GetCommand::move_file_or_dir(&final_out_dir, &previous_dir)
+                .map_err(|e| CommandError::IOError { file: final_out_dir.display().to_string(), message: format!("Failed to move existing folder aside: {}", e) })?;
+        }
+        let previous_modules = workspace.installed_modules.clone();
+        let res = Self::move_into_place(staged_module_dir, &final_out_dir)
+            .and_then(|_| Self::register_module(workspace, &final_out_dir, package_name, version, replace_entry_in_index, source));
+        if let Err(e) = res {
+            println!("{}", format!("Failed to install {}-{}: {}", package_name, version, e).red());
+            Self::rollback_install(workspace, previous_modules, &final_out_dir, if has_previous { Some(&previous_dir) } else { None });
+            return Err(e);
+        }
+        println!("Installed module {} to {}", package_name, final_out_dir.display());
+        println!("{}", format!("{}-{} installed successfully", package_name, version).as_str().green());
+        Ok(true)
+    }
+
+    // Clones a git repository at the given tag, branch or commit and registers the modules in it
+    fn run_install_git(&self, url: &str, rev: Option<&String>, output_dir: &PathBuf, prefix: Option<&String>) -> CommandResult {
+        check_online("Installing from git")?;
+        let mut workspace = Workspace::get()?;
+        let install_dir = output_dir.join(prefix.cloned().unwrap_or(git::get_repo_name(url)));
+        let final_out_dir = if install_dir.is_relative() { workspace.root.join(install_dir) } else { install_dir };
+        if final_out_dir.exists() {
+            return Err(InvalidArgumentError { message: format!("{} already exists. Remove it first or use --prefix to install to another folder.", final_out_dir.display()) });
+        }
+        let staging_root = get_nosman_dir_for(&workspace.root).join("staging");
+        fs::create_dir_all(&staging_root)?;
+        let staging_dir = tempfile::Builder::new().prefix("git").tempdir_in(&staging_root)?;
+        let clone_dir = staging_dir.path().join("module");
+        println!("Cloning {}{}", url, rev.map(|r| format!(" at {}", r)).unwrap_or_default());
+        git::clone(url, rev, &clone_dir)?;
+        let commit = git::get_head_commit(&clone_dir)?;
+        if get_module_manifests(&clone_dir).is_empty() {
+            return Err(GenericError { message: format!("{} does not contain a module manifest", url) });
+        }
+
+        let previous_modules = workspace.installed_modules.clone();
+        let res = Self::move_into_place(&clone_dir, &final_out_dir).and_then(|_| {
+            let registered = workspace.register_git_checkout(&final_out_dir, url, rev, &commit);
+            if registered.is_empty() {
+                return Err(GenericError { message: format!("No modules could be registered from {}", final_out_dir.display()) });
+            }
+            println!("Adding to workspace file");
+            workspace.save()?;
+            workspace.update_lockfile()?;
+            Ok(registered)
+        });
+        if let Err(e) = res {
+            println!("{}", format!("Failed to install from {}: {}", url, e).red());
+            Self::rollback_install(&mut workspace, previous_modules, &final_out_dir, None);
+            return Err(e);
+        }
+        for id in res.unwrap() {
+            println!("{}", format!("{} installed successfully from {} ({})", id, url, commit).as_str().green());
+        }
+        Ok(true)
+    }
+
+    // Installs a module from a local archive or folder, without any remote
+    fn run_install_from(&self, source_path: &PathBuf, output_dir: &PathBuf, prefix: Option<&String>) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        Self::install_from(&mut workspace, source_path, output_dir, prefix)
+    }
+
+    fn install_from(workspace: &mut Workspace, source_path: &PathBuf, output_dir: &PathBuf, prefix: Option<&String>) -> CommandResult {
+        if !source_path.exists() {
+            return Err(InvalidArgumentError { message: format!("{} does not exist", source_path.display()) });
+        }
+        let staging_root = get_nosman_dir_for(&workspace.root).join("staging");
+        fs::create_dir_all(&staging_root)?;
+        let staging_dir = tempfile::Builder::new().prefix("local").tempdir_in(&staging_root)?;
+        let extracted_dir = staging_dir.path().join("module");
+        if source_path.is_dir() {
+            println!("Copying {}", source_path.display());
+            fs_more::directory::copy_directory(source_path, &extracted_dir, fs_more::directory::DirectoryCopyOptions::default())
+                .map_err(|e| CommandError::IOError { file: source_path.display().to_string(), message: format!("Failed to copy module: {}", e) })?;
         } else {
-            return Err(GenericError { message: format!("None of the remotes contain package {} version {}. You can try rescan command to update index.", package_name, version) });
+            println!("Extracting {}", source_path.display());
+            let file = fs::File::open(source_path)?;
+            archive::extract(file, &source_path.display().to_string(), &extracted_dir)?;
+        }
+
+        let manifests = get_module_manifests(&extracted_dir);
+        if manifests.len() != 1 {
+            return Err(InvalidArgumentError { message: format!("{} must contain exactly one module manifest, found {}", source_path.display(), manifests.len()) });
+        }
+        let (_ty, manifest_path) = &manifests[0];
+        let content = fs::read_to_string(manifest_path)?;
+        let manifest: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| GenericError { message: format!("Failed to parse module manifest {}: {}", manifest_path.display(), e) })?;
+        let id = &manifest["info"]["id"];
+        let (package_name, version) = match (id["name"].as_str(), id["version"].as_str()) {
+            (Some(name), Some(version)) => (name.to_string(), version.to_string()),
+            _ => return Err(GenericError { message: format!("Module manifest {} does not contain info.id.name and info.id.version", manifest_path.display()) }),
+        };
+
+        let mut replace_entry_in_index = false;
+        if let Some(existing) = workspace.get_installed_module(&package_name, &version) {
+            if workspace.root.join(existing.get_module_dir()).exists() {
+                return Err(InvalidArgumentError { message: format!("Module {} version {} is already installed at {}. Remove it first.", package_name, version, existing.get_module_dir().display()) });
+            }
+            replace_entry_in_index = true;
+        }
+        let install_dir = match prefix {
+            Some(p) => output_dir.join(p),
+            None => output_dir.join(format!("{}-{}", package_name, version)),
+        };
+        let final_out_dir = if install_dir.is_relative() { workspace.root.join(install_dir) } else { install_dir };
+        // The module may be in a subfolder of the archive
+        let module_dir = manifest_path.parent().unwrap().to_path_buf();
+        Self::place_staged_module(workspace, &staging_dir.path().to_path_buf(), &module_dir, &final_out_dir, &package_name, &version, replace_entry_in_index, LockedModule::local(&package_name, &version))
+    }
+
+    // Checks that the extracted archive contains the manifest of the module
+    fn validate_staged_module(extracted_dir: &PathBuf, package_name: &str, version: &str) -> Result<(), CommandError> {
+        let manifests = get_module_manifests(extracted_dir);
+        if manifests.is_empty() {
+            return Err(GenericError { message: format!("Archive of {}-{} does not contain a module manifest", package_name, version) });
+        }
+        for (_ty, manifest_path) in &manifests {
+            let content = fs::read_to_string(manifest_path)?;
+            let manifest: serde_json::Value = serde_json::from_str(&content)
+                .map_err(|e| GenericError { message: format!("Failed to parse module manifest {}: {}", manifest_path.display(), e) })?;
+            let id = &manifest["info"]["id"];
+            if id["name"].as_str() == Some(package_name) && id["version"].as_str() == Some(version) {
+                return Ok(());
+            }
+        }
+        Err(GenericError { message: format!("Archive of {}-{} does not contain a manifest for module {} version {}", package_name, version, package_name, version) })
+    }
+
+    fn move_into_place(extracted_dir: &PathBuf, final_out_dir: &PathBuf) -> Result<(), CommandError> {
+        if let Some(parent) = final_out_dir.parent() {
+            fs::create_dir_all(parent)?;
         }
+        GetCommand::move_file_or_dir(extracted_dir, final_out_dir)
+            .map_err(|e| CommandError::IOError { file: final_out_dir.display().to_string(), message: format!("Failed to move module into place: {}", e) })
+    }
+
+    fn register_module(workspace: &mut Workspace, module_dir: &PathBuf, package_name: &str, version: &str, replace_entry_in_index: bool, source: LockedModule) -> Result<(), CommandError> {
+        workspace.scan_modules_in_folder(module_dir.clone(), replace_entry_in_index);
+        if workspace.get_installed_module(package_name, version).is_none() {
+            return Err(GenericError { message: format!("Module {} version {} could not be registered from {}", package_name, version, module_dir.display()) });
+        }
+        println!("Adding to workspace file");
+        workspace.save()?;
+        let mut lockfile = Lockfile::load(&workspace.root)?;
+        lockfile.set(source);
+        lockfile.sync_with(workspace);
+        lockfile.save(&workspace.root)?;
+        Ok(())
+    }
+
+    fn rollback_install(workspace: &mut Workspace, previous_modules: HashMap<String, HashMap<String, InstalledModule>>, final_out_dir: &PathBuf, previous_dir: Option<&PathBuf>) {
+        println!("{}", "Rolling back changes".yellow());
+        if final_out_dir.exists() {
+            println!("{}", format!("Rolling back: Remove {}", final_out_dir.display()).yellow().dimmed());
+            let _ = fs::remove_dir_all(final_out_dir);
+        }
+        if let Some(previous_dir) = previous_dir {
+            println!("{}", format!("Rolling back: Restore {}", final_out_dir.display()).yellow().dimmed());
+            if let Err(e) = GetCommand::move_file_or_dir(previous_dir, final_out_dir) {
+                println!("{}", format!("Failed to rollback: {}", e).red());
+            }
+        }
+        workspace.installed_modules = previous_modules;
+        if let Err(e) = workspace.save().and_then(|_| workspace.update_lockfile()) {
+            println!("{}", format!("Failed to rollback workspace file: {}", e).red());
+        }
+        println!("{}", "Rollback complete".yellow());
     }
 }
 
@@ -105,11 +560,76 @@ impl Command for InstallCommand {
     }
 
     fn run(&self, args: &ArgMatches) -> CommandResult {
-        let module_name = args.get_one::<String>("module").unwrap();
+        let module_name = args.get_one::<String>("module");
         let version = args.get_one::<String>("version");
         let output_dir = args.get_one::<String>("out_dir").map(|p| PathBuf::from(p)).unwrap_or_else(|| PathBuf::from("."));
         let prefix = args.get_one::<String>("prefix");
         let exact = args.get_one::<bool>("exact").unwrap().clone();
-        self.run_install(module_name, version, exact, &output_dir, prefix)
+        let no_deps = args.get_one::<bool>("no_deps").unwrap().clone();
+        let locked = args.get_one::<bool>("locked").unwrap().clone();
+        let ignore_api_version = args.get_one::<bool>("ignore_api_version").unwrap().clone();
+        let include_pre = args.get_one::<bool>("pre").unwrap().clone();
+        let platform = parse_platform_or_host(args.get_one::<String>("platform")).map_err(|message| InvalidArgumentError { message })?;
+        if let Some(from) = args.get_one::<String>("from") {
+            return self.run_install_from(&PathBuf::from(from), &output_dir, prefix);
+        }
+        let rev = args.get_one::<String>("rev");
+        if let Some(url) = args.get_one::<String>("git") {
+            return self.run_install_git(url, rev, &output_dir, prefix);
+        }
+        if let Some((url, spec_rev)) = module_name.and_then(|m| git::parse_git_spec(m)) {
+            if spec_rev.is_some() && rev.is_some() {
+                return Err(InvalidArgumentError { message: "Revision is given both in the git URL and with --rev".to_string() });
+            }
+            return self.run_install_git(&url, spec_rev.as_ref().or(rev), &output_dir, prefix);
+        }
+        if rev.is_some() {
+            return Err(InvalidArgumentError { message: "--rev can only be used when installing from git".to_string() });
+        }
+        if module_name.is_none() {
+            if !locked {
+                return Err(InvalidArgumentError { message: "Module name is required unless --locked or --from is set".to_string() });
+            }
+            return self.run_install_locked(&output_dir);
+        }
+        self.run_install(module_name.unwrap(), version, exact, &output_dir, prefix, !no_deps, locked, ignore_api_version, include_pre, &platform)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Appends an entry with the name as it is, tar::Builder would normalize './'
+    fn append_raw<W: std::io::Write>(builder: &mut tar::Builder<W>, name: &str, entry_type: tar::EntryType, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_mode(if entry_type.is_dir() { 0o755 } else { 0o644 });
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    #[test]
+    fn install_from_tarball_with_root_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap().join("workspace");
+        fs::create_dir_all(&root).unwrap();
+        let mut workspace = Workspace::new_empty(root.clone());
+        workspace.save().unwrap();
+
+        // As packed by CI with 'tar czf test.a.tar.gz -C dir .'
+        let tarball = dir.path().join("test.a.tar.gz");
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(fs::File::create(&tarball).unwrap(), flate2::Compression::default()));
+        let manifest = br#"{"info": {"id": {"name": "test.a", "version": "1.0.0"}}}"#;
+        append_raw(&mut builder, "./", tar::EntryType::Directory, &[]);
+        append_raw(&mut builder, "./test.a.noscfg", tar::EntryType::Regular, manifest);
+        builder.into_inner().unwrap().finish().unwrap();
+
+        InstallCommand::install_from(&mut workspace, &tarball, &PathBuf::from("Module"), None).unwrap();
+        let module = workspace.get_installed_module("test.a", "1.0.0").unwrap();
+        assert!(root.join(module.get_module_dir()).join("test.a.noscfg").exists());
+        assert_eq!(Lockfile::load(&root).unwrap().find("test.a", "1.0.0"), Some(&LockedModule::local("test.a", "1.0.0")));
     }
 }
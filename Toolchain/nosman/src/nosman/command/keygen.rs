@@ -0,0 +1,72 @@
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandError, CommandResult};
+use crate::nosman::command::CommandError::InvalidArgumentError;
+use crate::nosman::constants;
+use crate::nosman::signing::{encode_public_key, encode_signing_key, generate_signing_key};
+
+pub struct KeygenCommand {
+}
+
+impl KeygenCommand {
+    // Secret key is only readable by the owner
+    fn write_secret_key(path: &PathBuf, content: &str) -> io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        // mode only applies to newly created files, --force may overwrite an existing one
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(content.as_bytes())
+    }
+
+    fn run_keygen(&self, output: &PathBuf, force: bool) -> CommandResult {
+        let public_key_path = PathBuf::from(format!("{}.pub", output.display()));
+        if !force && (output.exists() || public_key_path.exists()) {
+            return Err(InvalidArgumentError { message: format!("{} or {} already exists. Use --force to overwrite.", output.display(), public_key_path.display()) });
+        }
+        if let Some(parent) = output.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let key = generate_signing_key();
+        let public_key = encode_public_key(&key.verifying_key());
+        Self::write_secret_key(output, &encode_signing_key(&key)).map_err(|e| CommandError::IOError { file: output.display().to_string(), message: format!("{}", e) })?;
+        fs::write(&public_key_path, &public_key).map_err(|e| CommandError::IOError { file: public_key_path.display().to_string(), message: format!("{}", e) })?;
+        println!("Secret key written to {}. Keep it private.", output.display());
+        println!("Public key written to {}", public_key_path.display());
+        println!("{}", format!("Public key: {}", public_key).green());
+        println!("Sign releases with 'publish --signing-key {}' or by setting {}.", output.display(), constants::SIGNING_KEY_ENV_VAR);
+        Ok(true)
+    }
+}
+
+impl Command for KeygenCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("keygen")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let output = PathBuf::from(args.get_one::<String>("output").unwrap());
+        let force = args.get_one::<bool>("force").unwrap();
+        self.run_keygen(&output, *force)
+    }
+
+    fn needs_workspace(&self) -> bool {
+        false
+    }
+}
@@ -11,7 +11,8 @@ impl ListCommand {
         let workspace = Workspace::get()?;
         for (name, ver_map) in &workspace.installed_modules {
             for (version, module) in ver_map {
-                println!("{} ({})", format!("{}-{}", name, version).green().to_string(), module.get_module_dir().display());
+                let marking = if module.installed_as_dependency { " [dependency]".dimmed().to_string() } else { String::new() };
+                println!("{} ({}){}", format!("{}-{}", name, version).green().to_string(), module.get_module_dir().display(), marking);
             }
         }
         Ok(true)
@@ -0,0 +1,42 @@
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::InvalidArgumentError;
+use crate::nosman::workspace::Workspace;
+
+pub struct MarkCommand {
+}
+
+impl MarkCommand {
+    fn run_mark(&self, module_name: &str, version: Option<&String>, installed_as_dependency: bool) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        let versions: Vec<String> = match version {
+            Some(version) => vec![version.clone()],
+            None => workspace.get_installed_modules(module_name).iter().map(|m| m.info.id.version.clone()).collect(),
+        };
+        if versions.is_empty() || versions.iter().any(|v| workspace.get_installed_module(module_name, v).is_none()) {
+            return Err(InvalidArgumentError { message: format!("Module {} {}is not installed", module_name, version.map(|v| format!("version {} ", v)).unwrap_or_default()) });
+        }
+        let marking = if installed_as_dependency { "installed as a dependency" } else { "explicitly installed" };
+        for v in &versions {
+            workspace.set_installed_as_dependency(module_name, v, installed_as_dependency);
+            println!("{}", format!("Marked {}-{} as {}", module_name, v, marking).green());
+        }
+        workspace.save()?;
+        Ok(true)
+    }
+}
+
+impl Command for MarkCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("mark")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let module_name = args.get_one::<String>("module").unwrap();
+        let version = args.get_one::<String>("version");
+        let dependency = args.get_one::<bool>("dependency").unwrap();
+        self.run_mark(module_name, version, *dependency)
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::index::SemVer;
+use crate::nosman::platform::get_host_platform;
+use crate::nosman::workspace::Workspace;
+
+pub(crate) struct OutdatedModule {
+    pub name: String,
+    pub installed: String,
+    // Newest version within the same major/minor version, if newer than the installed one
+    pub compatible: Option<String>,
+    // Newest version in the index, if newer than the installed one
+    pub latest: Option<String>,
+    pub module_dir: PathBuf,
+}
+
+// Compares installed modules (all of them if names is empty) against the cached index
+pub(crate) fn find_outdated(workspace: &Workspace, names: &Vec<String>) -> Vec<OutdatedModule> {
+    let mut outdated = Vec::new();
+    let host_platform = get_host_platform();
+    for (name, versions) in &workspace.installed_modules {
+        if !names.is_empty() && !names.contains(name) {
+            continue;
+        }
+        for (version, module) in versions {
+            // Modules installed from git are not compared against the index
+            if module.source.is_some() {
+                continue;
+            }
+            let installed = SemVer::parse_from_string(version);
+            if installed.is_none() {
+                continue;
+            }
+            let installed = installed.unwrap();
+            let version_start = SemVer::new(installed.major, installed.minor, None, None);
+            let version_end = version_start.get_one_up();
+            let is_newer = |v: &String| SemVer::parse_from_string(v).map(|v| v > installed).unwrap_or(false);
+            let compatible = workspace.index_cache.get_latest_compatible_release_within_range(name, &version_start, &version_end, &host_platform)
+                .map(|(_, release)| release.version.clone())
+                .filter(&is_newer);
+            let latest = workspace.index_cache.get_latest_release(name, &host_platform)
+                .map(|(_, release)| release.version.clone())
+                .filter(&is_newer);
+            if compatible.is_none() && latest.is_none() {
+                continue;
+            }
+            outdated.push(OutdatedModule {
+                name: name.clone(),
+                installed: version.clone(),
+                compatible,
+                latest,
+                module_dir: workspace.root.join(module.get_module_dir()),
+            });
+        }
+    }
+    outdated.sort_by(|a, b| a.name.cmp(&b.name).then(a.installed.cmp(&b.installed)));
+    outdated
+}
+
+pub struct OutdatedCommand {
+}
+
+impl OutdatedCommand {
+    fn run_outdated(&self) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        let names: Vec<String> = workspace.installed_modules.keys().cloned().collect();
+        workspace.fetch_package_releases_of(&names);
+        workspace.save()?;
+
+        let outdated = find_outdated(&workspace, &vec![]);
+        if outdated.is_empty() {
+            println!("{}", "All modules are up to date".green());
+            return Ok(true);
+        }
+        println!("{}", format!("{:<40} {:<16} {:<16} {:<16}", "Module", "Installed", "Compatible", "Latest").bold());
+        for module in &outdated {
+            let compatible = module.compatible.clone().unwrap_or("-".to_string());
+            let latest = module.latest.clone().unwrap_or("-".to_string());
+            println!("{:<40} {:<16} {} {}", module.name, module.installed, format!("{:<16}", compatible).green(), format!("{:<16}", latest).yellow());
+        }
+        println!("Run 'upgrade' to install the newer compatible versions.");
+        Ok(true)
+    }
+}
+
+impl Command for OutdatedCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("outdated")
+    }
+
+    fn run(&self, _args: &ArgMatches) -> CommandResult {
+        self.run_outdated()
+    }
+}
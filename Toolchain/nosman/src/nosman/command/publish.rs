@@ -25,11 +25,13 @@ use chrono::{Utc};
 
 use crate::nosman::command::{Command, CommandError, CommandResult};
 use crate::nosman::command::CommandError::{GenericError, InvalidArgumentError};
+use crate::nosman::common::{check_online, sha256_of};
 use crate::nosman::constants;
 use crate::nosman::index::{PackageReleaseEntry, PackageType, SemVer};
 use crate::nosman::module::PackageIdentifier;
 use crate::nosman::path::{get_plugin_manifest_file, get_subsystem_manifest_file};
 use crate::nosman::platform::{get_host_platform, Platform};
+use crate::nosman::signing::{encode_public_key, get_signing_key_path, load_signing_key, sign_release};
 use crate::nosman::workspace::Workspace;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -44,11 +46,12 @@ impl GlobsOrPlatformSpecificGlobs {
         match unresolved {
             GlobsOrPlatformSpecificGlobs::Globs(globs) => globs.clone(),
             GlobsOrPlatformSpecificGlobs::PlatformSpecificGlobs(platform_globs) => {
-                let platform_globs = platform_globs.get(&target_platform.to_string());
+                // Keys may be written in any accepted form, e.g. 'amd64-windows'
+                let platform_globs = platform_globs.iter().find(|(platform, _)| Platform::from_str(platform).as_ref() == Some(target_platform));
                 if platform_globs.is_none() {
                     return vec![];
                 }
-                platform_globs.unwrap().clone()
+                platform_globs.unwrap().1.clone()
             }
         }
     }
@@ -224,7 +227,11 @@ impl PublishCommand {
     }
     pub fn run_publish(&self, dry_run: bool, verbose: bool, path: &PathBuf, mut name: Option<String>, mut version: Option<String>, version_suffix: &String,
                    mut package_type: Option<PackageType>, remote_name: &String, vendor: Option<&String>,
-                   publisher_name: Option<&String>, publisher_email: Option<&String>, release_tags: &Vec<String>, opt_target_platform: Option<&String>) -> CommandResult {
+                   publisher_name: Option<&String>, publisher_email: Option<&String>, release_tags: &Vec<String>, opt_target_platform: Option<&String>,
+                   signing_key_path: Option<&PathBuf>) -> CommandResult {
+        if !dry_run {
+            check_online("Publishing")?;
+        }
         // Check if git and gh is installed.
         let git_installed = std::process::Command::new("git")
             .arg("--version")
@@ -247,7 +254,11 @@ impl PublishCommand {
             println!("{}", format!("Target platform is not provided. Using the current platform: {}", current_platform).yellow());
             current_platform
         } else {
-            Platform::from_str(opt_target_platform.unwrap()).expect("Invalid target platform")
+            let res = Platform::from_str(opt_target_platform.unwrap());
+            if res.is_none() {
+                return Err(InvalidArgumentError { message: format!("Invalid target platform: {}", opt_target_platform.unwrap()) });
+            }
+            res.unwrap()
         };
 
         if !path.exists() {
@@ -272,7 +283,7 @@ impl PublishCommand {
             if !found {
                 println!("{}", format!("No {} file found in {}. All files will be included in the release.", constants::PUBLISH_OPTIONS_FILE_NAME, abs_path.display()).as_str().yellow());
             } else if let Some(targets) = publish_options.target_platforms {
-                if !targets.contains(&target_platform.to_string()) {
+                if !targets.iter().any(|t| Platform::from_str(t).as_ref() == Some(&target_platform)) {
                     println!("{}", format!("Target platform {} is not in the list of target platforms in {}", target_platform.to_string(), constants::PUBLISH_OPTIONS_FILE_NAME).as_str().yellow());
                     return Ok(false);
                 }
@@ -320,6 +331,9 @@ impl PublishCommand {
                 module_tags = manifest["info"]["tags"].as_array().map(|a| a.iter().map(|v| v.as_str().unwrap().to_string()).collect());
                 let binary_path = manifest["binary_path"].as_str();
                 if binary_path.is_some() {
+                    if target_platform.is_any() {
+                        return Err(InvalidArgumentError { message: format!("Modules with a binary can not be published for platform {}", target_platform) });
+                    }
                     // Binary path is relative to the manifest file
                     let module_dir = manifest_file.parent().unwrap();
                     let binary_path = module_dir.join(binary_path.unwrap());
@@ -371,7 +385,7 @@ impl PublishCommand {
                         let mut minor = 0;
                         let mut patch = 0;
                         get_api_version_func(&mut major, &mut minor, &mut patch);
-                        api_version_opt = Some(SemVer { major: (major as u32), minor: Some(minor as u32), patch: Some(patch as u32), build_number: None });
+                        api_version_opt = Some(SemVer::new(major as u32, Some(minor as u32), Some(patch as u32), None));
                         println!("{}", format!("{} uses Nodos {:?} API version: {}.{}.{}", name.as_ref().unwrap(), &package_type, major, minor, patch).as_str().yellow());
 
                         {
@@ -418,6 +432,10 @@ impl PublishCommand {
         if None == SemVer::parse_from_string(version.as_str()) {
             return Err(InvalidArgumentError { message: format!("Version should be semantic-versioning compatible: {}", version) });
         }
+        let signing_key = match signing_key_path {
+            Some(p) => Some(load_signing_key(p)?),
+            None => None,
+        };
         let workspace = Workspace::get()?;
         let artifact_file_path;
         let temp_dir = tempdir().unwrap();
@@ -441,7 +459,7 @@ impl PublishCommand {
             }
 
             let host_platform = get_host_platform();
-            if target_platform.os != host_platform.os {
+            if !target_platform.is_any() && target_platform.os != host_platform.os {
                 pb.println(format!("Target OS ({}) is different from host OS ({}). Using hosts archive format.", target_platform.os, host_platform.os).yellow().to_string().as_str());
             }
 
@@ -511,6 +529,16 @@ impl PublishCommand {
             artifact_file_path = abs_path.clone();
         }
 
+        pb.set_message("Computing SHA-256 digest".to_string());
+        let mut artifact_file = File::open(&artifact_file_path).expect(format!("Failed to open file: {}", artifact_file_path.display()).as_str());
+        let sha256 = sha256_of(&mut artifact_file)?;
+        pb.println(format!("SHA-256: {}", sha256).as_str());
+        let release_platform = Some(target_platform.to_string());
+        let signature = signing_key.as_ref().map(|key| {
+            pb.println(format!("Signing with key {}", encode_public_key(&key.verifying_key())).as_str());
+            sign_release(key, &name, &version, release_platform.as_ref(), &sha256)
+        });
+
         // Create index entry for the release
         let remote = workspace.find_remote(remote_name);
         if remote.is_none() {
@@ -535,7 +563,11 @@ impl PublishCommand {
             category,
             module_tags,
             release_tags: if release_tags.is_empty() { None } else { Some(release_tags.clone()) },
-            platform: Some(target_platform.to_string()),
+            platform: release_platform,
+            sha256: Some(sha256),
+            signature,
+            source_remote: None,
+            source_vendor: None,
         };
         if verbose {
             println!("Release entry: {:?}", release);
@@ -581,7 +613,8 @@ impl Command for PublishCommand {
         let release_tags_ref: Vec<&String> = args.get_many::<String>("tag").unwrap_or_default().collect();
         let release_tags: Vec<String> = release_tags_ref.iter().map(|s| s.to_string()).collect();
         let target_platform: Option<&String> = args.get_one::<String>("target_platform");
-        self.run_publish(*dry_run, *verbose, &path, name, version, version_suffix, package_type, &remote_name, vendor, publisher_name, publisher_email, &release_tags, target_platform)
+        let signing_key_path = get_signing_key_path(args.get_one::<String>("signing_key"));
+        self.run_publish(*dry_run, *verbose, &path, name, version, version_suffix, package_type, &remote_name, vendor, publisher_name, publisher_email, &release_tags, target_platform, signing_key_path.as_ref())
     }
 
     fn needs_workspace(&self) -> bool {
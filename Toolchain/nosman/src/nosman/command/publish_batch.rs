@@ -8,6 +8,7 @@ use crate::nosman::command::CommandError::{InvalidArgumentError};
 use crate::nosman::command::publish::{PublishCommand, PublishOptions};
 use crate::nosman::constants;
 use crate::nosman::module::get_module_manifests;
+use crate::nosman::signing::get_signing_key_path;
 
 use path_slash::PathExt as _;
 
@@ -17,7 +18,8 @@ pub struct PublishBatchCommand {
 impl PublishBatchCommand {
     fn run_publish_batch(&self, dry_run: bool, verbose: bool, remote_name: &String, repo_path: &PathBuf, compare_with: Option<&String>,
                         version_suffix: &String, vendor: Option<&String>, publisher_name: Option<&String>,
-                        publisher_email: Option<&String>, release_tags: &Vec<String>, target_platform: Option<&String>) -> CommandResult {
+                        publisher_email: Option<&String>, release_tags: &Vec<String>, target_platform: Option<&String>,
+                        signing_key_path: Option<&PathBuf>) -> CommandResult {
         if !repo_path.exists() {
             return Err(InvalidArgumentError { message: format!("Repo {} does not exist", repo_path.display()) });
         }
@@ -102,7 +104,7 @@ impl PublishBatchCommand {
             return Ok(true);
         }
         for module_root in to_be_published {
-            PublishCommand {}.run_publish(dry_run, verbose, &module_root, None, None, version_suffix, None, remote_name, vendor, publisher_name, publisher_email, release_tags, target_platform)?;
+            PublishCommand {}.run_publish(dry_run, verbose, &module_root, None, None, version_suffix, None, remote_name, vendor, publisher_name, publisher_email, release_tags, target_platform, signing_key_path)?;
         }
 
         return Ok(true);
@@ -132,7 +134,8 @@ impl Command for PublishBatchCommand {
         let release_tags_ref: Vec<&String> = args.get_many::<String>("tag").unwrap_or_default().collect();
         let release_tags: Vec<String> = release_tags_ref.iter().map(|s| s.to_string()).collect();
         let target_platform = args.get_one::<String>("target_platform");
-        self.run_publish_batch(*dry_run, *verbose, &remote_name, &repo_path, opt_compare_with, &version_suffix, vendor, publisher_name, publisher_email, &release_tags, target_platform)
+        let signing_key_path = get_signing_key_path(args.get_one::<String>("signing_key"));
+        self.run_publish_batch(*dry_run, *verbose, &remote_name, &repo_path, opt_compare_with, &version_suffix, vendor, publisher_name, publisher_email, &release_tags, target_platform, signing_key_path.as_ref())
     }
 
     fn needs_workspace(&self) -> bool {
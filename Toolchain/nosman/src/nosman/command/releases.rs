@@ -0,0 +1,81 @@
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::InvalidArgumentError;
+use crate::nosman::index::PackageReleaseEntry;
+use crate::nosman::workspace::Workspace;
+
+pub struct ReleasesCommand {
+}
+
+fn describe_release(release: &PackageReleaseEntry) -> Vec<String> {
+    let mut details = Vec::new();
+    details.push(format!("platform: {}", release.platform.clone().unwrap_or("any".to_string())));
+    if let Some(date) = &release.release_date {
+        details.push(format!("released: {}", date));
+    }
+    if let Some(api_version) = &release.plugin_api_version {
+        details.push(format!("plugin API: {}", api_version.to_string()));
+    }
+    if let Some(api_version) = &release.subsystem_api_version {
+        details.push(format!("subsystem API: {}", api_version.to_string()));
+    }
+    if let Some(dependencies) = &release.dependencies {
+        if !dependencies.is_empty() {
+            let deps: Vec<String> = dependencies.iter().map(|d| format!("{} {}", d.name, d.version)).collect();
+            details.push(format!("dependencies: {}", deps.join(", ")));
+        }
+    }
+    if let Some(tags) = &release.release_tags {
+        if !tags.is_empty() {
+            details.push(format!("tags: {}", tags.join(", ")));
+        }
+    }
+    if let Some(remote) = &release.source_remote {
+        details.push(format!("remote: {}", remote));
+    }
+    details
+}
+
+impl ReleasesCommand {
+    fn run_releases(&self, package_name: &str, json: bool) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        workspace.fetch_package_releases(package_name);
+        workspace.save()?;
+        let res = workspace.index_cache.get_releases(package_name);
+        if res.is_none() {
+            return Err(InvalidArgumentError { message: format!("Package {} not found in any remote", package_name) });
+        }
+        let (package_type, releases) = res.unwrap();
+        if json {
+            let json = serde_json::json!({
+                "name": package_name,
+                "type": package_type,
+                "releases": releases,
+            });
+            println!("{}", serde_json::to_string_pretty(&json).unwrap());
+            return Ok(true);
+        }
+        println!("{}", format!("{} ({:?}): {} release(s)", package_name, package_type, releases.len()).bold());
+        for release in &releases {
+            println!("  {}", release.version.green());
+            for detail in describe_release(release) {
+                println!("      {}", detail.dimmed());
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl Command for ReleasesCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("releases")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let package_name = args.get_one::<String>("package").unwrap();
+        let json = args.get_one::<bool>("json").unwrap();
+        self.run_releases(package_name, *json)
+    }
+}
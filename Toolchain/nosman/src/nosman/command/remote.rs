@@ -3,6 +3,8 @@ use colored::Colorize;
 
 use crate::nosman;
 use crate::nosman::command::{Command, CommandError, CommandResult};
+use crate::nosman::index::TrustedKey;
+use crate::nosman::signing;
 use crate::nosman::workspace::Workspace;
 
 pub struct RemoteAddCommand {
@@ -57,6 +59,9 @@ impl RemoteListCommand {
         println!("{}", "Remotes".green());
         for remote in &workspace.remotes {
             println!("  {} - {}", remote.name, remote.url);
+            for key in &remote.trusted_keys {
+                println!("    {} {}", format!("trusts {}:", key.vendor).dimmed(), key.public_key);
+            }
         }
 
         Ok(true)
@@ -77,3 +82,48 @@ impl Command for RemoteListCommand {
 }
 
 
+
+pub struct RemoteTrustCommand {
+}
+
+impl RemoteTrustCommand {
+    fn run_trust(&self, remote_name_or_url: &str, vendor: &str, public_key: &str) -> Result<bool, CommandError> {
+        if let Err(e) = signing::parse_public_key(public_key) {
+            return Err(CommandError::InvalidArgumentError { message: e });
+        }
+        let mut workspace = Workspace::get()?;
+        let remote = workspace.remotes.iter_mut().find(|r| r.url == remote_name_or_url || r.name == remote_name_or_url);
+        if remote.is_none() {
+            return Err(CommandError::InvalidArgumentError { message: format!("Remote {} not found", remote_name_or_url) });
+        }
+        let remote = remote.unwrap();
+        let key = TrustedKey { vendor: vendor.to_string(), public_key: public_key.trim().to_string() };
+        if remote.trusted_keys.contains(&key) {
+            println!("Key is already trusted for vendor {} on remote {}", vendor, remote.url);
+            return Ok(true);
+        }
+        remote.trusted_keys.push(key);
+        let remote_url = remote.url.clone();
+
+        workspace.save().map_err(|e| CommandError::IOError { file: workspace.get_nosman_index_filepath().display().to_string(), message: format!("{}", e) })?;
+
+        println!("Trusted key added for vendor {} on remote {}", vendor, remote_url);
+        Ok(true)
+    }
+}
+
+impl Command for RemoteTrustCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        if let Some(subcommand) = args.subcommand_matches("remote") {
+            return subcommand.subcommand_matches("trust");
+        }
+        None
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let remote = args.get_one::<String>("remote").unwrap();
+        let vendor = args.get_one::<String>("vendor").unwrap();
+        let public_key = args.get_one::<String>("public_key").unwrap();
+        self.run_trust(remote, vendor, public_key)
+    }
+}
@@ -1,18 +1,55 @@
+use std::collections::HashSet;
 use clap::{ArgMatches};
+use colored::Colorize;
 
 use crate::nosman;
 use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::InvalidArgumentError;
+use crate::nosman::module::PackageIdentifier;
 
 use nosman::workspace::Workspace;
 
 pub struct RemoveCommand {
 }
 
+fn join_ids(ids: &Vec<PackageIdentifier>) -> String {
+    ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(", ")
+}
+
 impl RemoveCommand {
-    fn run_remove(&self, module_name: &str, version: &str) -> CommandResult {
+    fn run_remove(&self, module_name: &str, version: &str, force: bool, cascade: bool) -> CommandResult {
         // Fetch remotes
         let mut workspace = Workspace::get()?;
-        workspace.remove(module_name, version)
+        if workspace.get_installed_module(module_name, version).is_none() {
+            return Err(InvalidArgumentError { message: format!("Module {} version {} is not installed", module_name, version) });
+        }
+        let target = PackageIdentifier { name: module_name.to_string(), version: version.to_string() };
+        let mut removed: HashSet<PackageIdentifier> = HashSet::from([target.clone()]);
+        let dependents = workspace.get_dependents(&removed);
+        if dependents.is_empty() {
+            return workspace.remove(module_name, version);
+        }
+        if !force && !cascade {
+            return Err(InvalidArgumentError { message: format!("Module {} version {} is required by: {}. Use --force to remove it anyway or --cascade to remove them too.",
+                                                               module_name, version, join_ids(&dependents)) });
+        }
+        if !cascade {
+            println!("{}", format!("Removing {}-{} even though it is required by: {}", module_name, version, join_ids(&dependents)).yellow());
+            return workspace.remove(module_name, version);
+        }
+        // Dependents of the dependents are removed too, in the reverse order they are found
+        let mut to_remove = vec![target];
+        let mut current = dependents;
+        while !current.is_empty() {
+            for id in &current {
+                removed.insert(id.clone());
+                to_remove.push(id.clone());
+            }
+            current = workspace.get_dependents(&removed);
+        }
+        to_remove.reverse();
+        println!("{}", format!("Removing {} module(s): {}", to_remove.len(), join_ids(&to_remove)).yellow());
+        workspace.remove_modules(&to_remove)
     }
 }
 
@@ -24,6 +61,8 @@ impl Command for RemoveCommand {
     fn run(&self, args: &ArgMatches) -> CommandResult {
         let module_name = args.get_one::<String>("module").unwrap();
         let version = args.get_one::<String>("version").unwrap();
-        self.run_remove(module_name, version)
+        let force = args.get_one::<bool>("force").unwrap();
+        let cascade = args.get_one::<bool>("cascade").unwrap();
+        self.run_remove(module_name, version, *force, *cascade)
     }
 }
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use clap::{ArgMatches};
 use crate::nosman::command::{Command, CommandResult};
 use crate::nosman::command::install::InstallCommand;
+use crate::nosman::platform::get_host_platform;
 
 // Hashmap of sample names to package names
 pub (crate) static SAMPLES: phf::Map<&'static str, &'static str> = phf::phf_map! {
@@ -18,7 +19,7 @@ impl SampleCommand {
     fn run_get_sample(&self, name: &str, output_dir: &PathBuf) -> CommandResult {
         let opt_pkg_name = SAMPLES.get(name);
         if let Some(pkg_name) = opt_pkg_name {
-            InstallCommand{}.run_install(pkg_name, None, true, output_dir, None)
+            InstallCommand{}.run_install(pkg_name, None, true, output_dir, None, true, false, false, false, &get_host_platform())
         } else {
             Err (crate::nosman::command::CommandError::GenericError { message: format!("Sample {} not found", name) })
         }
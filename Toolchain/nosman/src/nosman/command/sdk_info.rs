@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 use crate::nosman::command::{Command, CommandError, CommandResult};
 use crate::nosman::path::get_default_engines_dir;
 use crate::nosman::workspace;
-use crate::nosman::index::SemVer;
+use crate::nosman::index::{PackageReleaseEntry, SemVer};
+use crate::nosman::version_req::{BareVersion, VersionReq};
 
 pub struct SdkInfoCommand {
 }
@@ -67,6 +68,47 @@ pub fn get_engine_sdk_infos() -> Result<Vec<SdkInfo>, CommandError> {
     Ok(result)
 }
 
+// Plugin and subsystem API versions the engines in the workspace can load
+pub struct EngineApiVersions {
+    plugin: Vec<SemVer>,
+    subsystem: Vec<SemVer>,
+}
+
+impl EngineApiVersions {
+    // Returns None if there are no engines in the workspace to check against
+    pub fn of_workspace() -> Option<EngineApiVersions> {
+        let engines = get_engine_sdk_infos().ok()?;
+        if engines.is_empty() {
+            return None;
+        }
+        Some(EngineApiVersions {
+            plugin: engines.iter().filter_map(|e| SemVer::parse_from_string(&e.plugin_sdk_version)).collect(),
+            subsystem: engines.iter().filter_map(|e| SemVer::parse_from_string(&e.subsystem_sdk_version)).collect(),
+        })
+    }
+
+    // A release can be loaded if an engine provides the same major API version with an equal or newer minor version.
+    // Returns the reason if it can not be loaded.
+    pub fn check(&self, release: &PackageReleaseEntry) -> Result<(), String> {
+        let join = |versions: &Vec<SemVer>| versions.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(", ");
+        if let Some(required) = &release.plugin_api_version {
+            if !self.plugin.iter().any(|v| v.satisfies_requested_version(required)) {
+                return Err(format!("it requires plugin API {} but the installed engines provide {}", required.to_string(), join(&self.plugin)));
+            }
+        }
+        if let Some(required) = &release.subsystem_api_version {
+            if !self.subsystem.iter().any(|v| v.satisfies_requested_version(required)) {
+                return Err(format!("it requires subsystem API {} but the installed engines provide {}", required.to_string(), join(&self.subsystem)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn can_load(&self, release: &PackageReleaseEntry) -> bool {
+        self.check(release).is_ok()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 struct SdkInfoOutput {
     version: String,
@@ -96,16 +138,18 @@ impl SdkInfoCommand {
             b_sem_ver.cmp(&a_sem_ver)
         });
 
-        let requested_sem_ver = match SemVer::parse_from_string(requested_version) {
-            Some(semver) => semver,
-            None => return Err(CommandError::InvalidArgumentError { message: format!("Invalid version: {}", requested_version) }),
+        // A bare version means a compatible one: same major version, equal or newer minor version
+        // Installed SDKs are never skipped for being pre-releases
+        let req = match VersionReq::parse_with(requested_version, BareVersion::SameMajor) {
+            Ok(req) => req.including_pre_releases(true),
+            Err(e) => return Err(CommandError::InvalidArgumentError { message: e }),
         };
 
         let mut found_sdk_info: Option<SdkInfoOutput> = None;
         // Determine the correct version key based on sdk_type
         for sdk_info in selected_versions {
             let sdk_sem_ver = SemVer::parse_from_string(&sdk_info.version).expect("Failed to parse SDK version");
-            if sdk_sem_ver.satisfies_requested_version(&requested_sem_ver) {
+            if req.matches(&sdk_sem_ver) {
                 found_sdk_info = Some(sdk_info);
                 break;
             }
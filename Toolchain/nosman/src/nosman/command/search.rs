@@ -0,0 +1,154 @@
+use std::time::Duration;
+use clap::{ArgMatches};
+use colored::Colorize;
+use indicatif::ProgressBar;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::InvalidArgumentError;
+use crate::nosman::index::{PackageIndexEntry, PackageReleaseEntry, PackageType};
+use crate::nosman::platform::{parse_platform_or_host, Platform};
+use crate::nosman::workspace::Workspace;
+
+pub struct SearchCommand {
+}
+
+pub(crate) struct SearchFilters {
+    pub text: Option<String>,
+    pub vendor: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub package_type: Option<PackageType>,
+    pub platform: Platform,
+}
+
+impl SearchFilters {
+    fn matches_package(&self, package: &PackageIndexEntry) -> bool {
+        if let Some(text) = &self.text {
+            let text = text.to_lowercase();
+            if !package.name.to_lowercase().contains(&text) && !package.vendor.to_lowercase().contains(&text) {
+                return false;
+            }
+        }
+        if let Some(vendor) = &self.vendor {
+            if !package.vendor.eq_ignore_ascii_case(vendor) {
+                return false;
+            }
+        }
+        if let Some(package_type) = &self.package_type {
+            if package.package_type != *package_type {
+                return false;
+            }
+        }
+        true
+    }
+    fn matches_release(&self, release: &PackageReleaseEntry) -> bool {
+        if release.is_pre_release() {
+            return false;
+        }
+        if !release.is_for_platform(&self.platform) {
+            return false;
+        }
+        if let Some(category) = &self.category {
+            if !release.category.as_ref().map(|c| c.eq_ignore_ascii_case(category)).unwrap_or(false) {
+                return false;
+            }
+        }
+        for tag in &self.tags {
+            let in_module_tags = release.module_tags.as_ref().map(|t| t.iter().any(|t| t.eq_ignore_ascii_case(tag))).unwrap_or(false);
+            let in_release_tags = release.release_tags.as_ref().map(|t| t.iter().any(|t| t.eq_ignore_ascii_case(tag))).unwrap_or(false);
+            if !in_module_tags && !in_release_tags {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl SearchCommand {
+    fn run_search(&self, filters: &SearchFilters) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        let pb = ProgressBar::new_spinner();
+        pb.enable_steady_tick(Duration::from_millis(100));
+        let mut packages: Vec<PackageIndexEntry> = Vec::new();
+        for remote in &workspace.remotes {
+            pb.set_message(format!("Fetching remote {}", remote.name));
+            let res = remote.fetch(&workspace);
+            if let Err(e) = res {
+                pb.println(format!("Failed to fetch remote: {}", e));
+                continue;
+            }
+            for package in res.unwrap() {
+                if filters.matches_package(&package) && !packages.iter().any(|p| p.name == package.name) {
+                    packages.push(package);
+                }
+            }
+        }
+        pb.finish_and_clear();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let names: Vec<String> = packages.iter().map(|p| p.name.clone()).collect();
+        if !names.is_empty() {
+            workspace.fetch_package_releases_of(&names);
+            workspace.save()?;
+        }
+
+        let mut found = 0;
+        for package in &packages {
+            let latest = workspace.index_cache.get_latest_release_matching(&package.name, |r| filters.matches_release(r));
+            if latest.is_none() {
+                continue;
+            }
+            let (_, release) = latest.unwrap();
+            found += 1;
+            println!("{} {} {}", package.name.green(), release.version, format!("({:?}, {})", package.package_type, package.vendor).dimmed());
+            let mut details = Vec::new();
+            if let Some(category) = &release.category {
+                details.push(format!("category: {}", category));
+            }
+            if let Some(tags) = &release.module_tags {
+                if !tags.is_empty() {
+                    details.push(format!("tags: {}", tags.join(", ")));
+                }
+            }
+            if let Some(platform) = &release.platform {
+                details.push(format!("platform: {}", platform));
+            }
+            if !details.is_empty() {
+                println!("    {}", details.join(" | ").dimmed());
+            }
+        }
+        if found == 0 {
+            println!("{}", "No packages found".yellow());
+        }
+        Ok(true)
+    }
+}
+
+impl Command for SearchCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("search")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let package_type = match args.get_one::<String>("type") {
+            Some(s) => {
+                let res = serde_json::from_str::<PackageType>(format!("\"{}\"", s).as_str());
+                if res.is_err() {
+                    return Err(InvalidArgumentError { message: format!("Unknown package type: {}", s) });
+                }
+                Some(res.unwrap())
+            }
+            None => None,
+        };
+        let platform = parse_platform_or_host(args.get_one::<String>("platform")).map_err(|message| InvalidArgumentError { message })?;
+        let filters = SearchFilters {
+            text: args.get_one::<String>("text").cloned(),
+            vendor: args.get_one::<String>("vendor").cloned(),
+            category: args.get_one::<String>("category").cloned(),
+            tags: args.get_many::<String>("tag").unwrap_or_default().cloned().collect(),
+            package_type,
+            platform,
+        };
+        self.run_search(&filters)
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::{GenericError, InvalidArgumentError};
+use crate::nosman::command::get::GetCommand;
+use crate::nosman::command::install::InstallCommand;
+use crate::nosman::command::sdk_info::get_engine_sdk_infos;
+use crate::nosman::constants;
+use crate::nosman::index::SemVer;
+use crate::nosman::lockfile::Lockfile;
+use crate::nosman::version_req::VersionReq;
+use crate::nosman::module::PackageIdentifier;
+use crate::nosman::platform::get_host_platform;
+use crate::nosman::workspace::Workspace;
+use crate::nosman::workspace_manifest::WorkspaceManifest;
+
+pub struct SyncCommand {
+}
+
+impl SyncCommand {
+    fn is_nodos_release_present(version: &str) -> bool {
+        let req = VersionReq::parse(version);
+        if req.is_err() {
+            return false;
+        }
+        let req = req.unwrap();
+        let engines = get_engine_sdk_infos();
+        if engines.is_err() {
+            return false;
+        }
+        engines.unwrap().iter().any(|engine| {
+            match SemVer::parse_from_string(&engine.version) {
+                Some(v) => req.matches(&v),
+                None => false,
+            }
+        })
+    }
+
+    fn run_sync(&self, check: bool) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        if !WorkspaceManifest::exists_in(&workspace.root) {
+            return Err(InvalidArgumentError { message: format!("No {} found under {}", constants::WORKSPACE_MANIFEST_FILE_NAME, workspace.root.display()) });
+        }
+        let manifest = WorkspaceManifest::load(&workspace.root)?;
+        let mut drift = 0;
+
+        // Remotes
+        for remote in &manifest.remotes {
+            if workspace.remotes.iter().any(|r| r.url == remote.url) {
+                continue;
+            }
+            drift += 1;
+            println!("{}", format!("Remote {} ({}) is missing", remote.name, remote.url).yellow());
+            if !check {
+                workspace.add_remote(remote.clone());
+                workspace.save()?;
+                println!("Remote added: {}", remote.url);
+            }
+        }
+        if !manifest.remotes.is_empty() {
+            for remote in &workspace.remotes {
+                if !manifest.remotes.iter().any(|r| r.url == remote.url) {
+                    println!("{}", format!("Remote {} ({}) is not listed in {}", remote.name, remote.url, constants::WORKSPACE_MANIFEST_FILE_NAME).dimmed());
+                }
+            }
+        }
+
+        // Nodos release
+        if let Some(nodos) = &manifest.nodos {
+            if !Self::is_nodos_release_present(&nodos.version) {
+                drift += 1;
+                println!("{}", format!("Nodos release {} version {} is missing", nodos.name, nodos.version).yellow());
+                if !check {
+                    // Sync does not stop at prompts
+                    GetCommand {}.run_get(&workspace.root, &nodos.name, Some(&nodos.version), true, true, false, &get_host_platform())?;
+                    workspace = Workspace::get()?;
+                }
+            }
+        }
+
+        // Modules listed in the manifest
+        let mut required: Vec<PackageIdentifier> = Vec::new();
+        for module in &manifest.modules {
+            match workspace.get_latest_installed_module_for_version(&module.name, &module.version) {
+                Ok(installed) => required.push(installed.info.id.clone()),
+                Err(_) => {
+                    drift += 1;
+                    println!("{}", format!("Module {} version {} is missing", module.name, module.version).yellow());
+                    if !check {
+                        InstallCommand {}.run_install(&module.name, Some(&module.version), false, &PathBuf::from("./Module"), None, true, false, false, false, &get_host_platform())?;
+                        workspace = Workspace::get()?;
+                        if let Ok(installed) = workspace.get_latest_installed_module_for_version(&module.name, &module.version) {
+                            required.push(installed.info.id.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        // Modules that are no longer listed. Only the ones installed from a remote are removed,
+        // and the dependencies of the kept ones are kept too.
+        let lockfile = Lockfile::load(&workspace.root)?;
+        let mut roots = required.clone();
+        for (name, versions) in &workspace.installed_modules {
+            for version in versions.keys() {
+                let id = PackageIdentifier { name: name.clone(), version: version.clone() };
+                let installed_from_remote = lockfile.find(name, version).map(|m| m.url.is_some()).unwrap_or(false);
+                if !installed_from_remote && !required.contains(&id) {
+                    println!("{}", format!("Module {} is not listed in {}, but it was not installed from a remote. Keeping it.", id, constants::WORKSPACE_MANIFEST_FILE_NAME).dimmed());
+                    roots.push(id);
+                }
+            }
+        }
+        let keep = workspace.get_installed_dependency_closure(&roots);
+        let mut unlisted: HashSet<PackageIdentifier> = workspace.installed_modules.values().flat_map(|versions| versions.values())
+            .map(|m| m.info.id.clone())
+            .filter(|id| !keep.contains(id))
+            .collect();
+        // Same as remove, a module is not removed while a kept module has no other installed version to depend on
+        loop {
+            let dependents = workspace.get_dependents(&unlisted);
+            let rescued: Vec<PackageIdentifier> = workspace.get_installed_dependency_closure(&dependents).into_iter()
+                .filter(|id| unlisted.contains(id))
+                .collect();
+            if rescued.is_empty() {
+                break;
+            }
+            for id in rescued {
+                println!("{}", format!("Module {} is not listed in {}, but other modules depend on it. Keeping it.", id, constants::WORKSPACE_MANIFEST_FILE_NAME).dimmed());
+                unlisted.remove(&id);
+            }
+        }
+        let mut unlisted: Vec<PackageIdentifier> = unlisted.into_iter().collect();
+        unlisted.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+        for id in &unlisted {
+            drift += 1;
+            println!("{}", format!("Module {} is no longer listed in {}", id, constants::WORKSPACE_MANIFEST_FILE_NAME).yellow());
+        }
+        if !check {
+            workspace.remove_modules(&unlisted)?;
+        }
+
+        if drift == 0 {
+            println!("{}", format!("Workspace is in sync with {}", constants::WORKSPACE_MANIFEST_FILE_NAME).green());
+        } else if check {
+            return Err(GenericError { message: format!("Workspace has {} difference(s) from {}. Run sync to apply them.", drift, constants::WORKSPACE_MANIFEST_FILE_NAME) });
+        } else {
+            println!("{}", format!("Workspace is synced with {} ({} change(s))", constants::WORKSPACE_MANIFEST_FILE_NAME, drift).green());
+        }
+        Ok(true)
+    }
+}
+
+impl Command for SyncCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("sync")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let check = args.get_one::<bool>("check").unwrap();
+        self.run_sync(*check)
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::InvalidArgumentError;
+use crate::nosman::module::PackageIdentifier;
+use crate::nosman::platform::get_host_platform;
+use crate::nosman::version_req::VersionReq;
+use crate::nosman::workspace::Workspace;
+
+// Modules that no other installed module depends on
+fn get_top_level_modules(workspace: &Workspace) -> Vec<PackageIdentifier> {
+    let mut required: HashSet<PackageIdentifier> = HashSet::new();
+    for versions in workspace.installed_modules.values() {
+        for module in versions.values() {
+            for dep in module.info.dependencies.clone().unwrap_or_default() {
+                if let Ok(dep_module) = workspace.get_latest_installed_module_for_version(&dep.name, &dep.version) {
+                    required.insert(dep_module.info.id.clone());
+                }
+            }
+        }
+    }
+    let mut top_level: Vec<PackageIdentifier> = workspace.installed_modules.values().flat_map(|versions| versions.values())
+        .map(|m| m.info.id.clone())
+        .filter(|id| !required.contains(id))
+        .collect();
+    top_level.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+    top_level
+}
+
+// Dependencies of an installed module, or of a release in the index if it is not installed
+fn get_dependencies(workspace: &Workspace, id: &PackageIdentifier) -> Vec<PackageIdentifier> {
+    if let Some(module) = workspace.get_installed_module(&id.name, &id.version) {
+        return module.info.dependencies.clone().unwrap_or_default();
+    }
+    if let Some((_, release)) = workspace.index_cache.get_package(&id.name, &id.version, &get_host_platform()) {
+        return release.dependencies.clone().unwrap_or_default();
+    }
+    vec![]
+}
+
+pub struct TreeCommand {
+}
+
+impl TreeCommand {
+    fn print_dependencies(&self, workspace: &Workspace, id: &PackageIdentifier, prefix: &str, path: &mut Vec<PackageIdentifier>) {
+        let dependencies = get_dependencies(workspace, id);
+        for (i, dep) in dependencies.iter().enumerate() {
+            let last = i == dependencies.len() - 1;
+            let branch = if last { "└── " } else { "├── " };
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            let req = VersionReq::parse(&dep.version);
+            if let Err(e) = req {
+                println!("{}{}{} {}", prefix, branch, dep, format!("(invalid version requirement: {})", e).red());
+                continue;
+            }
+            let req = req.unwrap();
+            if let Some(module) = workspace.get_latest_installed_module_matching(&dep.name, &req) {
+                let resolved = module.info.id.clone();
+                if path.contains(&resolved) {
+                    println!("{}{}{} {} {}", prefix, branch, dep.name, resolved.version.green(), "(cycle)".yellow());
+                    continue;
+                }
+                println!("{}{}{} {} {}", prefix, branch, dep.name, dep.version.dimmed(), format!("-> {}", resolved.version).green());
+                path.push(resolved.clone());
+                self.print_dependencies(workspace, &resolved, &child_prefix, path);
+                path.pop();
+                continue;
+            }
+            let installed: Vec<String> = workspace.get_installed_modules(&dep.name).iter().map(|m| m.info.id.version.clone()).collect();
+            if !installed.is_empty() {
+                println!("{}{}{} {} {}", prefix, branch, dep.name, dep.version, format!("(version mismatch, installed: {})", installed.join(", ")).red());
+                continue;
+            }
+            let host_platform = get_host_platform();
+            let available = workspace.index_cache.get_latest_release_matching(&dep.name, |r| r.is_for_platform(&host_platform) && req.matches_str(&r.version));
+            match available {
+                Some((_, release)) => {
+                    println!("{}{}{} {} {}", prefix, branch, dep.name, dep.version, format!("(missing, available: {})", release.version).red());
+                    let resolved = PackageIdentifier { name: dep.name.clone(), version: release.version.clone() };
+                    if !path.contains(&resolved) {
+                        path.push(resolved.clone());
+                        self.print_dependencies(workspace, &resolved, &child_prefix, path);
+                        path.pop();
+                    }
+                }
+                None => println!("{}{}{} {} {}", prefix, branch, dep.name, dep.version, "(missing)".red()),
+            }
+        }
+    }
+
+    fn run_tree(&self, module_name: Option<&String>, version: Option<&String>) -> CommandResult {
+        let workspace = Workspace::get()?;
+        let roots: Vec<PackageIdentifier> = match module_name {
+            Some(name) => {
+                let mut roots: Vec<PackageIdentifier> = workspace.get_installed_modules(name).iter()
+                    .map(|m| m.info.id.clone())
+                    .filter(|id| version.map(|v| *v == id.version).unwrap_or(true))
+                    .collect();
+                if roots.is_empty() {
+                    return Err(InvalidArgumentError { message: format!("Module {} {}is not installed", name, version.map(|v| format!("version {} ", v)).unwrap_or_default()) });
+                }
+                roots.sort_by(|a, b| a.version.cmp(&b.version));
+                roots
+            }
+            None => get_top_level_modules(&workspace),
+        };
+        for root in &roots {
+            println!("{}", root.to_string().green());
+            self.print_dependencies(&workspace, root, "", &mut vec![root.clone()]);
+        }
+        Ok(true)
+    }
+}
+
+impl Command for TreeCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("tree")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let module_name = args.get_one::<String>("module");
+        let version = args.get_one::<String>("version");
+        self.run_tree(module_name, version)
+    }
+}
+
+pub struct WhyCommand {
+}
+
+impl WhyCommand {
+    // Installed modules whose dependency resolves to the given module
+    fn get_direct_dependents(workspace: &Workspace, id: &PackageIdentifier) -> Vec<(PackageIdentifier, String)> {
+        let mut dependents = Vec::new();
+        for versions in workspace.installed_modules.values() {
+            for module in versions.values() {
+                for dep in module.info.dependencies.clone().unwrap_or_default() {
+                    if dep.name != id.name {
+                        continue;
+                    }
+                    if let Ok(dep_module) = workspace.get_latest_installed_module_for_version(&dep.name, &dep.version) {
+                        if dep_module.info.id == *id {
+                            dependents.push((module.info.id.clone(), dep.version.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        dependents.sort_by(|a, b| a.0.name.cmp(&b.0.name).then(a.0.version.cmp(&b.0.version)));
+        dependents
+    }
+
+    // Collects the chains of dependents from the modules nothing depends on down to chain[0]
+    fn collect_chains(workspace: &Workspace, chain: &mut Vec<(PackageIdentifier, Option<String>)>, chains: &mut Vec<Vec<(PackageIdentifier, Option<String>)>>) {
+        let current = chain.last().unwrap().0.clone();
+        let dependents = Self::get_direct_dependents(workspace, &current);
+        if dependents.is_empty() {
+            if chain.len() > 1 {
+                chains.push(chain.iter().rev().cloned().collect());
+            }
+            return;
+        }
+        for (dependent, requirement) in dependents {
+            if chain.iter().any(|(id, _)| *id == dependent) {
+                continue;
+            }
+            // The requirement belongs to the edge between the dependent and the module before it
+            chain.last_mut().unwrap().1 = Some(requirement);
+            chain.push((dependent, None));
+            Self::collect_chains(workspace, chain, chains);
+            chain.pop();
+        }
+    }
+
+    fn run_why(&self, module_name: &str, version: &str) -> CommandResult {
+        let workspace = Workspace::get()?;
+        let module = workspace.get_installed_module(module_name, version);
+        if module.is_none() {
+            return Err(InvalidArgumentError { message: format!("Module {} version {} is not installed", module_name, version) });
+        }
+        let module = module.unwrap();
+        let mut chains = Vec::new();
+        Self::collect_chains(&workspace, &mut vec![(module.info.id.clone(), None)], &mut chains);
+        if chains.is_empty() {
+            let reason = if module.installed_as_dependency { "it was installed as a dependency and can be removed with 'autoremove'" } else { "it was installed explicitly" };
+            println!("No installed module depends on {}-{}, {}", module_name, version, reason);
+            return Ok(true);
+        }
+        for chain in &chains {
+            let mut parts = Vec::new();
+            for (id, requirement) in chain {
+                match requirement {
+                    Some(requirement) => parts.push(format!("{} {}", id, format!("(required as {})", requirement).dimmed())),
+                    None => parts.push(id.to_string()),
+                }
+            }
+            println!("{}", parts.join(" -> "));
+        }
+        Ok(true)
+    }
+}
+
+impl Command for WhyCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("why")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let module_name = args.get_one::<String>("module").unwrap();
+        let version = args.get_one::<String>("version").unwrap();
+        self.run_why(module_name, version)
+    }
+}
@@ -2,6 +2,7 @@ use clap::{ArgMatches};
 use colored::Colorize;
 use crate::nosman;
 use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::common::check_online;
 
 use nosman::workspace::Workspace;
 use crate::nosman::command::CommandError::{GenericError, InvalidArgumentError};
@@ -11,6 +12,9 @@ pub struct UnpublishCommand {
 
 impl UnpublishCommand {
     fn run_unpublish(&self, dry_run: bool, verbose: bool, remote_name: &String, package_name: &String, version: Option<&String>) -> CommandResult {
+        if !dry_run {
+            check_online("Unpublishing")?;
+        }
         let workspace = Workspace::get()?;
 
         let remote = workspace.find_remote(remote_name);
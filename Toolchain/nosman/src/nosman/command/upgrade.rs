@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use clap::{ArgMatches};
+use colored::Colorize;
+
+use crate::nosman::command::{Command, CommandResult};
+use crate::nosman::command::CommandError::InvalidArgumentError;
+use crate::nosman::command::install::InstallCommand;
+use crate::nosman::command::outdated::find_outdated;
+use crate::nosman::common::is_offline;
+use crate::nosman::git;
+use crate::nosman::module::{ModuleSource, PackageIdentifier};
+use crate::nosman::platform::get_host_platform;
+use crate::nosman::workspace::Workspace;
+
+pub struct UpgradeCommand {
+}
+
+impl UpgradeCommand {
+    // Pulls the git checkouts that track a branch. Checkouts of a tag or a commit are left as they are.
+    fn upgrade_git_checkouts(&self, names: &Vec<String>) -> CommandResult {
+        let mut workspace = Workspace::get()?;
+        let mut checkouts: Vec<(String, Option<String>, PathBuf)> = Vec::new();
+        for (name, versions) in &workspace.installed_modules {
+            if !names.is_empty() && !names.contains(name) {
+                continue;
+            }
+            for module in versions.values() {
+                if let Some(ModuleSource::Git { url, rev, path, .. }) = &module.source {
+                    if !checkouts.iter().any(|(_, _, p)| p == path) {
+                        checkouts.push((url.clone(), rev.clone(), path.clone()));
+                    }
+                }
+            }
+        }
+        if is_offline() && !checkouts.is_empty() {
+            println!("{}", "Offline mode: git checkouts are not pulled".yellow());
+            return Ok(true);
+        }
+        for (url, rev, path) in checkouts {
+            let checkout_dir = workspace.root.join(&path);
+            if git::get_current_branch(&checkout_dir).is_none() {
+                println!("{}", format!("{} is pinned to {}, reinstall it with another revision to change it", path.display(), rev.unwrap_or("a commit".to_string())).dimmed());
+                continue;
+            }
+            println!("{}", format!("Pulling {}", path.display()).yellow());
+            git::pull(&checkout_dir)?;
+            let commit = git::get_head_commit(&checkout_dir)?;
+            for id in workspace.register_git_checkout(&checkout_dir, &url, rev.as_ref(), &commit) {
+                println!("{}", format!("{} is at {}", id, commit).green());
+            }
+        }
+        workspace.save()?;
+        workspace.update_lockfile()?;
+        Ok(true)
+    }
+
+    fn run_upgrade(&self, names: &Vec<String>, remove_old: bool) -> CommandResult {
+        let workspace = Workspace::get()?;
+        for name in names {
+            if !workspace.installed_modules.contains_key(name) {
+                return Err(InvalidArgumentError { message: format!("Module {} is not installed", name) });
+            }
+        }
+        self.upgrade_git_checkouts(names)?;
+        let mut workspace = Workspace::get()?;
+        let fetched: Vec<String> = if names.is_empty() { workspace.installed_modules.keys().cloned().collect() } else { names.clone() };
+        workspace.fetch_package_releases_of(&fetched);
+        workspace.save()?;
+
+        let upgrades: Vec<_> = find_outdated(&workspace, names).into_iter().filter(|m| m.compatible.is_some()).collect();
+        if upgrades.is_empty() {
+            println!("{}", "All modules are up to date".green());
+            return Ok(true);
+        }
+        for module in &upgrades {
+            let new_version = module.compatible.as_ref().unwrap();
+            println!("{}", format!("Upgrading {} {} -> {}", module.name, module.installed, new_version).yellow());
+            // Install next to the module being upgraded
+            let output_dir = module.module_dir.parent().map(|p| p.to_path_buf()).unwrap_or(workspace.root.clone());
+            InstallCommand {}.run_install(&module.name, Some(new_version), true, &output_dir, None, true, false, false, false, &get_host_platform())?;
+            // The new version is marked the same way as the old one
+            let installed_as_dependency = workspace.get_installed_module(&module.name, &module.installed).map(|m| m.installed_as_dependency).unwrap_or(false);
+            if installed_as_dependency {
+                let mut workspace = Workspace::get()?;
+                workspace.set_installed_as_dependency(&module.name, new_version, true);
+                workspace.save()?;
+            }
+            if remove_old {
+                let mut workspace = Workspace::get()?;
+                let old = PackageIdentifier { name: module.name.clone(), version: module.installed.clone() };
+                let dependents = workspace.get_dependents(&HashSet::from([old]));
+                if !dependents.is_empty() {
+                    println!("{}", format!("Keeping {} {}: it is still required by {}", module.name, module.installed,
+                                           dependents.iter().map(|d| d.to_string()).collect::<Vec<String>>().join(", ")).yellow());
+                    continue;
+                }
+                workspace.remove(&module.name, &module.installed)?;
+                // The new version replaces the old one in the lockfile
+                workspace.remove_from_lockfile(&module.name, &module.installed)?;
+            }
+        }
+        for module in &upgrades {
+            let newer = module.latest.as_ref().filter(|latest| Some(*latest) != module.compatible.as_ref());
+            if let Some(latest) = newer {
+                println!("{}", format!("{} {} is available, but it is not compatible with {}. Install it explicitly to use it.", module.name, latest, module.installed).dimmed());
+            }
+        }
+        println!("{}", format!("Upgraded {} module(s)", upgrades.len()).green());
+        Ok(true)
+    }
+}
+
+impl Command for UpgradeCommand {
+    fn matched_args<'a>(&self, args : &'a ArgMatches) -> Option<&'a ArgMatches> {
+        args.subcommand_matches("upgrade")
+    }
+
+    fn run(&self, args: &ArgMatches) -> CommandResult {
+        let names: Vec<String> = args.get_many::<String>("module").unwrap_or_default().cloned().collect();
+        let remove_old = args.get_one::<bool>("remove_old").unwrap();
+        self.run_upgrade(&names, *remove_old)
+    }
+}
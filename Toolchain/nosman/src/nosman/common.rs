@@ -1,56 +1,187 @@
-use std::fs;
 use std::fs::File;
 use std::io::{Read, Seek};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::process::Output;
+use std::sync::OnceLock;
+use std::time::Duration;
 use colored::Colorize;
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
 use inquire::Confirm;
-use zip::ZipArchive;
+use sha2::{Digest, Sha256};
+use crate::nosman::{archive, cache};
 use crate::nosman::command::CommandError;
+use crate::nosman::constants;
+use crate::nosman::signing::SignatureCheck;
 
-pub fn download_and_extract(url: &str, target: &PathBuf) -> Result<(), CommandError> {
-    let mut tmpfile = tempfile::tempfile().expect("Failed to create tempfile");
-    reqwest::blocking::get(url)
-    .expect(format!("Failed to fetch {}", url).as_str()).copy_to(&mut tmpfile)
-    .expect(format!("Failed to write to {:?}", tmpfile).as_str());
+pub fn sha256_of<R: Read>(reader: &mut R) -> Result<String, std::io::Error> {
+    let mut hasher = Sha256::new();
+    std::io::copy(reader, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
 
-    tmpfile.seek(std::io::SeekFrom::Start(0)).expect("Failed to seek to start of file");
+static OFFLINE: OnceLock<bool> = OnceLock::new();
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.set(offline).unwrap();
+}
+
+pub fn is_offline() -> bool {
+    *OFFLINE.get().unwrap_or(&false)
+}
+
+pub fn is_offline_env_set() -> bool {
+    match std::env::var(constants::OFFLINE_ENV_VAR) {
+        Ok(value) => ["1", "true", "yes", "on"].contains(&value.trim().to_lowercase().as_str()),
+        Err(_) => false,
+    }
+}
+
+// Fails if nosman is in offline mode. 'what' describes the operation that needs the network.
+pub fn check_online(what: &str) -> Result<(), CommandError> {
+    if is_offline() {
+        return Err(CommandError::GenericError { message: format!("{} requires network access, but nosman is in offline mode (--offline or {} is set)", what, constants::OFFLINE_ENV_VAR) });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkOptions {
+    pub connect_timeout: Duration,
+    // Limit for a single request, including reading the response. None means no limit.
+    pub timeout: Option<Duration>,
+    // Number of retries after a failed download
+    pub retries: u32,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        NetworkOptions { connect_timeout: Duration::from_secs(30), timeout: None, retries: 5 }
+    }
+}
+
+static NETWORK_OPTIONS: OnceLock<NetworkOptions> = OnceLock::new();
+
+pub fn set_network_options(options: NetworkOptions) {
+    NETWORK_OPTIONS.set(options).unwrap();
+}
+
+pub fn get_network_options() -> NetworkOptions {
+    NETWORK_OPTIONS.get().cloned().unwrap_or_default()
+}
+
+pub fn http_client() -> Result<reqwest::blocking::Client, CommandError> {
+    let options = get_network_options();
+    reqwest::blocking::Client::builder()
+        .connect_timeout(options.connect_timeout)
+        .timeout(options.timeout)
+        .build()
+        .map_err(|e| CommandError::GenericError { message: format!("Failed to create HTTP client: {}", e) })
+}
+
+// Error of a single download attempt. Retryable ones are retried (resuming from where they left off if possible).
+enum DownloadError {
+    Retryable(String),
+    Fatal(String),
+}
 
-    // If tar.gz, use flate2 to extract
-    if url.ends_with(".tar.gz") {
-        #[cfg(unix)]
-        {
-            let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(tmpfile));
-            fs::create_dir_all(target.clone())?;
-            archive.unpack(&target)?;
-            return Ok(());
+fn download_attempt(client: &reqwest::blocking::Client, url: &str, file: &mut File, pb: &ProgressBar) -> Result<(), DownloadError> {
+    let mut offset = file.metadata().map_err(|e| DownloadError::Fatal(e.to_string()))?.len();
+    let mut request = client.get(url);
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let response = request.send().map_err(|e| DownloadError::Retryable(e.to_string()))?;
+    let status = response.status();
+    if offset > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT {
+        pb.println(format!("Resuming download from {}", HumanBytes(offset)));
+    } else if status.is_success() {
+        if offset > 0 {
+            pb.println("Server does not support resuming downloads, starting over".yellow().to_string());
         }
+        offset = 0;
+    } else if offset > 0 && status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        file.set_len(0).map_err(|e| DownloadError::Fatal(e.to_string()))?;
+        return Err(DownloadError::Retryable(format!("HTTP {}", status)));
+    } else if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::REQUEST_TIMEOUT {
+        return Err(DownloadError::Retryable(format!("HTTP {}", status)));
+    } else {
+        return Err(DownloadError::Fatal(format!("HTTP {}", status)));
     }
-    
-    let mut archive = ZipArchive::new(tmpfile)?;
-    fs::create_dir_all(target.clone())?;
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let filename = file.name();
-        // It might contain \, so convert this to POSIX compatible path
-        let filename = filename.replace("\\", "/");
-        let outpath = Path::new(&target).join(filename);
-
-        if file.is_dir() {
-            fs::create_dir_all(&outpath)?;
-        } else {
-            if let Some(parent) = outpath.parent() {
-                if !parent.exists() {
-                    fs::create_dir_all(parent)?;
+    file.set_len(offset).map_err(|e| DownloadError::Fatal(e.to_string()))?;
+    file.seek(std::io::SeekFrom::Start(offset)).map_err(|e| DownloadError::Fatal(e.to_string()))?;
+
+    let total = response.content_length().map(|len| offset + len);
+    match total {
+        Some(total) => {
+            pb.set_style(ProgressStyle::with_template("{msg} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, ETA {eta})").unwrap().progress_chars("=> "));
+            pb.set_length(total);
+        }
+        None => pb.set_style(ProgressStyle::with_template("{spinner} {msg} {bytes} ({bytes_per_sec})").unwrap()),
+    }
+    pb.set_position(offset);
+    let mut reader = pb.wrap_read(response);
+    std::io::copy(&mut reader, file).map_err(|e| DownloadError::Retryable(e.to_string()))?;
+    if let Some(total) = total {
+        let written = file.metadata().map_err(|e| DownloadError::Fatal(e.to_string()))?.len();
+        if written != total {
+            return Err(DownloadError::Retryable(format!("Transfer ended early ({} of {})", HumanBytes(written), HumanBytes(total))));
+        }
+    }
+    Ok(())
+}
+
+// Downloads url into file, retrying with exponential backoff on failures.
+pub fn download(url: &str, file: &mut File) -> Result<(), CommandError> {
+    check_online(format!("Downloading {} (not found in the download cache)", url).as_str())?;
+    let client = http_client()?;
+    let retries = get_network_options().retries;
+    let pb = ProgressBar::new(0);
+    pb.set_message(format!("Downloading {}", url.rsplit('/').next().unwrap_or(url)));
+    let mut attempt = 0;
+    loop {
+        match download_attempt(&client, url, file, &pb) {
+            Ok(()) => break,
+            Err(DownloadError::Fatal(msg)) => {
+                pb.finish_and_clear();
+                return Err(CommandError::GenericError { message: format!("Failed to download {}: {}", url, msg) });
+            }
+            Err(DownloadError::Retryable(msg)) => {
+                if attempt >= retries {
+                    pb.finish_and_clear();
+                    return Err(CommandError::GenericError { message: format!("Failed to download {} after {} attempt(s): {}", url, attempt + 1, msg) });
                 }
+                let delay = Duration::from_secs(1 << attempt.min(5));
+                attempt += 1;
+                pb.println(format!("Download of {} failed: {}. Retrying in {}s ({}/{})", url, msg, delay.as_secs(), attempt, retries).yellow().to_string());
+                std::thread::sleep(delay);
             }
-            let mut outfile = fs::File::create(&outpath)?;
-            std::io::copy(&mut file, &mut outfile)?;
         }
     }
+    pb.finish_and_clear();
     Ok(())
 }
 
+// Gets the archive at url (from the download cache if possible), checks its SHA-256 digest against expected_sha256 and its signature (if provided) and extracts it to target.
+// Returns the SHA-256 digest of the downloaded archive.
+pub fn download_and_extract(url: &str, target: &PathBuf, expected_sha256: Option<&String>, signature_check: Option<&SignatureCheck>) -> Result<String, CommandError> {
+    let (mut tmpfile, digest) = cache::get_or_download(url, expected_sha256, |file| download(url, file))?;
+    match expected_sha256 {
+        Some(expected) => {
+            if !expected.eq_ignore_ascii_case(&digest) {
+                return Err(CommandError::GenericError { message: format!("SHA-256 mismatch for {}: expected {}, got {}. The download might be incomplete or corrupted.", url, expected, digest) });
+            }
+        }
+        None => println!("{}", format!("No SHA-256 digest available for {}, skipping integrity check", url).yellow()),
+    }
+    if let Some(check) = signature_check {
+        check.verify(url, &digest)?;
+    }
+    tmpfile.seek(std::io::SeekFrom::Start(0)).expect("Failed to seek to start of file");
+
+    archive::extract(tmpfile, url, target)?;
+    Ok(digest)
+}
+
 pub fn check_file_contents_same(path1: &PathBuf, path2: &PathBuf) -> bool {
     // Efficiently compare file contents
     let mut file1 = File::open(path1).expect(format!("Failed to open {:?}", path1).as_str());
@@ -9,5 +9,12 @@ pub(crate) static NODE_DEF_FILE_EXT: &str = "nosdef";
 
 pub(crate) static PUBLISH_OPTIONS_FILE_NAME: &str = ".nospub";
 
+pub(crate) static LOCKFILE_NAME: &str = "nodos.lock";
+pub(crate) static WORKSPACE_MANIFEST_FILE_NAME: &str = "nodos.json";
+
+pub(crate) static SIGNING_KEY_ENV_VAR: &str = "NOSMAN_SIGNING_KEY";
+pub(crate) static CACHE_DIR_ENV_VAR: &str = "NOSMAN_CACHE_DIR";
+pub(crate) static OFFLINE_ENV_VAR: &str = "NOSMAN_OFFLINE";
+
 pub(crate) static POSSIBLE_CAN_SHOW_AS: [&str; 7] = ["PROPERTY_ONLY", "INPUT_PIN_ONLY", "INPUT_PIN_OR_PROPERTY", "OUTPUT_PIN_OR_PROPERTY", "OUTPUT_PIN_ONLY", "INPUT_OUTPUT", "INPUT_OUTPUT_PROPERTY"];
 pub(crate) static POSSIBLE_SHOW_AS: [&str; 3] = ["INPUT_PIN", "OUTPUT_PIN", "PROPERTY"];
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+use crate::nosman::command::CommandError;
+
+fn run_git(dir: Option<&PathBuf>, args: &[&str]) -> Result<String, CommandError> {
+    let mut cmd = std::process::Command::new("git");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let output = cmd.args(args).output();
+    if let Err(e) = output {
+        return Err(CommandError::GenericError { message: format!("Failed to run git {}: {}", args.join(" "), e) });
+    }
+    let output = output.unwrap();
+    if !output.status.success() {
+        return Err(CommandError::GenericError { message: format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()) });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Index where the path of the repository URL starts, i.e. after the scheme and the host
+// ('ssh://git@host/org/repo' or 'git@host:org/repo'), so that the user part is not taken as a revision
+fn get_path_start(url: &str) -> usize {
+    if let Some(scheme_end) = url.find("://") {
+        let authority_start = scheme_end + 3;
+        return url[authority_start..].find('/').map(|i| authority_start + i).unwrap_or(url.len());
+    }
+    // scp-like syntax, a drive letter (e.g. C:/repo) is a local path
+    match url.find(':') {
+        Some(i) if i > 1 => i + 1,
+        _ => 0,
+    }
+}
+
+// Splits 'git+<url>[@<rev>]' into the repository URL and the optional tag, branch or commit.
+// The revision may contain '/', e.g. git+https://host/org/repo.git@feature/x
+pub fn parse_git_spec(spec: &str) -> Option<(String, Option<String>)> {
+    let url = spec.strip_prefix("git+")?;
+    let path_start = get_path_start(url);
+    match url[path_start..].rfind('@') {
+        Some(i) => Some((url[..path_start + i].to_string(), Some(url[path_start + i + 1..].to_string()))),
+        None => Some((url.to_string(), None)),
+    }
+}
+
+// Name of the repository in the URL, without the .git suffix
+pub fn get_repo_name(url: &str) -> String {
+    let name = url.trim_end_matches('/').rsplit(|c| c == '/' || c == ':').next().unwrap_or(url);
+    name.trim_end_matches(".git").to_string()
+}
+
+pub fn clone(url: &str, rev: Option<&String>, target: &PathBuf) -> Result<(), CommandError> {
+    // User supplied values must not be taken as options
+    if let Some(rev) = rev.filter(|r| r.starts_with('-')) {
+        return Err(CommandError::InvalidArgumentError { message: format!("Invalid revision: {}", rev) });
+    }
+    run_git(None, &["clone", "--", url, target.to_str().unwrap()])?;
+    if let Some(rev) = rev {
+        run_git(Some(target), &["checkout", rev])?;
+    }
+    Ok(())
+}
+
+pub fn get_head_commit(dir: &PathBuf) -> Result<String, CommandError> {
+    run_git(Some(dir), &["rev-parse", "HEAD"])
+}
+
+// Returns None if HEAD is detached, i.e. a tag or a commit is checked out
+pub fn get_current_branch(dir: &PathBuf) -> Option<String> {
+    run_git(Some(dir), &["symbolic-ref", "--short", "-q", "HEAD"]).ok()
+}
+
+pub fn has_local_changes(dir: &PathBuf) -> bool {
+    run_git(Some(dir), &["status", "--porcelain"]).map(|s| !s.is_empty()).unwrap_or(false)
+}
+
+pub fn pull(dir: &PathBuf) -> Result<(), CommandError> {
+    run_git(Some(dir), &["pull", "--ff-only"]).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(url: &str, rev: Option<&str>) -> Option<(String, Option<String>)> {
+        Some((url.to_string(), rev.map(|r| r.to_string())))
+    }
+
+    #[test]
+    fn parse_git_spec_splits_revision() {
+        assert_eq!(parse_git_spec("git+https://github.com/org/repo.git"), spec("https://github.com/org/repo.git", None));
+        assert_eq!(parse_git_spec("git+https://github.com/org/repo.git@v1.2.0"), spec("https://github.com/org/repo.git", Some("v1.2.0")));
+        assert_eq!(parse_git_spec("git+https://github.com/org/repo.git@feature/x"), spec("https://github.com/org/repo.git", Some("feature/x")));
+        assert_eq!(parse_git_spec("nos.sys.vulkan"), None);
+    }
+
+    #[test]
+    fn parse_git_spec_keeps_user_in_url() {
+        assert_eq!(parse_git_spec("git+ssh://git@github.com/org/repo.git"), spec("ssh://git@github.com/org/repo.git", None));
+        assert_eq!(parse_git_spec("git+ssh://git@github.com/org/repo.git@feature/x"), spec("ssh://git@github.com/org/repo.git", Some("feature/x")));
+        assert_eq!(parse_git_spec("git+git@github.com:org/repo.git"), spec("git@github.com:org/repo.git", None));
+        assert_eq!(parse_git_spec("git+git@github.com:org/repo.git@main"), spec("git@github.com:org/repo.git", Some("main")));
+    }
+}
@@ -7,9 +7,10 @@ use serde::{Deserialize, Serialize};
 use rayon::prelude::*;
 use crate::nosman::constants;
 use crate::nosman::workspace::Workspace;
-use crate::nosman::common::{run_if_not};
+use crate::nosman::common::{is_offline, run_if_not};
 use crate::nosman::module::{PackageIdentifier};
-use crate::nosman::platform::get_host_platform;
+use crate::nosman::platform::Platform;
+use crate::nosman::version_req::VersionReq;
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Hash, Clone)]
 pub enum PackageType {
@@ -39,7 +40,7 @@ pub struct PackageIndexEntry {
     pub(crate) name: String,
     #[serde(rename = "url")]
     pub(crate) releases_url: String,
-    vendor: String,
+    pub(crate) vendor: String,
     #[serde(rename = "type")]
     pub(crate) package_type: PackageType,
 }
@@ -60,6 +61,41 @@ pub struct SemVer {
     pub patch: Option<u32>,
     #[serde(alias = "build", alias = "BUILD", alias = "Build", skip_serializing_if = "Option::is_none")]
     pub build_number: Option<u32>,
+    // Dot separated identifiers after '-', e.g. 'rc.1' in 1.3.0-rc.1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_release: Option<String>,
+    // Dot separated identifiers after '+', e.g. 'cuda12' in 1.3.0+cuda12. Does not affect precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_metadata: Option<String>,
+}
+
+// Pre-release identifiers are compared one by one: numeric ones numerically and lower than alphanumeric ones,
+// which are compared in ASCII order. A shorter list of identifiers is lower if all preceding ones are equal.
+fn compare_pre_release(a: &str, b: &str) -> std::cmp::Ordering {
+    let mut a_ids = a.split('.');
+    let mut b_ids = b.split('.');
+    loop {
+        match (a_ids.next(), b_ids.next()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(a_id), Some(b_id)) => {
+                let ord = match (a_id.parse::<u64>(), b_id.parse::<u64>()) {
+                    (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+                    (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+                    (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+                    (Err(_), Err(_)) => a_id.cmp(b_id),
+                };
+                if ord != std::cmp::Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+fn is_valid_identifiers(s: &str) -> bool {
+    s.split('.').all(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
 }
 
 // Implement ordering for SemVer
@@ -93,7 +129,15 @@ impl PartialOrd for SemVer {
                 }
             }
         }
-        Some(std::cmp::Ordering::Equal)
+        // A pre-release version has lower precedence than the release
+        let pre_release_order = match (&self.pre_release, &other.pre_release) {
+            (None, None) => std::cmp::Ordering::Equal,
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (Some(a), Some(b)) => compare_pre_release(a, b),
+        };
+        // Build metadata does not affect precedence
+        Some(pre_release_order)
     }
 }
 
@@ -104,12 +148,28 @@ impl std::cmp::Ord for SemVer {
 }
 
 impl SemVer {
+    pub fn new(major: u32, minor: Option<u32>, patch: Option<u32>, build_number: Option<u32>) -> SemVer {
+        SemVer { major, minor, patch, build_number, pre_release: None, build_metadata: None }
+    }
     pub fn parse_from_string(s: &str) -> Option<SemVer> {
         // Parse 1.2.3.b4 -> (1, 2, 3, Some(4))
         // Parse 1.2.3.4 -> (1, 2, 3, Some(4))
         // Parse 1.2.3 -> (1, 2, 3, None)
         // Parse 1.2 -> (1, 2, 0, None)
         // Parse 1 -> (1, 0, 0, None)
+        // Parse 1.3.0-rc.1+cuda12 -> (1, 3, 0, None), pre-release 'rc.1', build metadata 'cuda12'
+        let (s, build_metadata) = match s.split_once('+') {
+            Some((version, metadata)) => (version, Some(metadata.to_string())),
+            None => (s, None),
+        };
+        let (s, pre_release) = match s.split_once('-') {
+            Some((version, pre)) => (version, Some(pre.to_string())),
+            None => (s, None),
+        };
+        if pre_release.as_ref().map(|p| !is_valid_identifiers(p)).unwrap_or(false)
+            || build_metadata.as_ref().map(|m| !is_valid_identifiers(m)).unwrap_or(false) {
+            return None;
+        }
         let parts: Vec<&str> = s.split('.').collect();
         let opt_major = parts.get(0).and_then(|s| s.parse::<u32>().ok());
         let opt_minor = parts.get(1).and_then(|s| s.parse::<u32>().ok());
@@ -129,6 +189,8 @@ impl SemVer {
             minor: opt_minor,
             patch: opt_patch,
             build_number: opt_build_number,
+            pre_release,
+            build_metadata,
         })
     }
     pub fn to_string(&self) -> String {
@@ -142,31 +204,25 @@ impl SemVer {
         if let Some(build_number) = self.build_number {
             s.push_str(&format!(".b{}", build_number));
         }
+        if let Some(pre_release) = &self.pre_release {
+            s.push_str(&format!("-{}", pre_release));
+        }
+        if let Some(build_metadata) = &self.build_metadata {
+            s.push_str(&format!("+{}", build_metadata));
+        }
         s
     }
+    pub fn is_pre_release(&self) -> bool {
+        self.pre_release.is_some()
+    }
     pub fn upper_minor(&self) -> SemVer {
-        SemVer {
-            major: self.major,
-            minor: self.minor.map(|m| m + 1),
-            patch: None,
-            build_number: None,
-        }
+        SemVer::new(self.major, self.minor.map(|m| m + 1), None, None)
     }
     pub fn upper_patch(&self) -> SemVer {
-        SemVer {
-            major: self.major,
-            minor: self.minor,
-            patch: self.patch.map(|p| p + 1),
-            build_number: None,
-        }
+        SemVer::new(self.major, self.minor, self.patch.map(|p| p + 1), None)
     }
     pub fn upper_build(&self) -> SemVer {
-        SemVer {
-            major: self.major,
-            minor: self.minor,
-            patch: self.patch,
-            build_number: self.build_number.map(|b| b + 1),
-        }
+        SemVer::new(self.major, self.minor, self.patch, self.build_number.map(|b| b + 1))
     }
     pub fn get_one_up(&self) -> SemVer {
         let version_start = self.clone();
@@ -179,14 +235,11 @@ impl SemVer {
         }
     }
 	pub fn satisfies_requested_version(&self, requested: &SemVer) -> bool {
-		if self.major != requested.major {
-			return false;
-		}
-		return self.minor >= requested.minor;
+		VersionReq::same_major_as(requested).matches(self)
 	}
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PackageReleaseEntry {
     pub(crate) version: String,
     pub(crate) url: String,
@@ -209,6 +262,31 @@ pub struct PackageReleaseEntry {
     pub release_tags: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub platform: Option<String>,
+    // SHA-256 digest of the release archive, as a lowercase hex string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    // Base64 encoded ed25519 signature of the release (see signing.rs), made with a signing key of the vendor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    // URL of the remote this release was fetched from. Not a part of the published release entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_remote: Option<String>,
+    // Vendor of the package in the remote's index. Not a part of the published release entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_vendor: Option<String>,
+}
+
+impl PackageReleaseEntry {
+    // Releases without a platform are platform independent
+    pub fn is_for_platform(&self, platform: &Platform) -> bool {
+        platform.match_release_platform(self.platform.as_ref()).is_some()
+    }
+    pub fn is_platform_independent(&self) -> bool {
+        self.platform.as_ref().and_then(|p| Platform::from_str(p)).map(|p| p.is_any()).unwrap_or(self.platform.is_none())
+    }
+    pub fn is_pre_release(&self) -> bool {
+        SemVer::parse_from_string(&self.version).map(|v| v.is_pre_release()).unwrap_or(false)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -217,10 +295,20 @@ pub struct PackageReleases {
     pub(crate) releases: Vec<PackageReleaseEntry>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct TrustedKey {
+    pub vendor: String,
+    // Base64 encoded ed25519 public key
+    pub public_key: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Remote {
     pub name: String,
     pub url: String,
+    // Releases of a vendor listed here must be signed with one of its keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_keys: Vec<TrustedKey>,
 }
 
 impl Remote {
@@ -228,6 +316,7 @@ impl Remote {
         Remote {
             name: name.to_string(),
             url: url.to_string(),
+            trusted_keys: vec![],
         }
     }
     pub fn fetch(&self, workspace: &Workspace) -> Result<Vec<PackageIndexEntry>, String> {
@@ -236,6 +325,9 @@ impl Remote {
             fs::create_dir_all(repo_dir.parent().unwrap()).unwrap();
         }
         if !repo_dir.exists() {
+            if is_offline() {
+                return Err(format!("Remote {} was never fetched, it can not be used in offline mode", self.name));
+            }
             let output = std::process::Command::new("git")
                 .arg("clone")
                 .arg(&self.url)
@@ -261,17 +353,22 @@ impl Remote {
             if let Err(e) = output {
                 return Err(format!("Failed to clean the remote repository: {}", e));
             }
-            let output = std::process::Command::new("git")
-                .current_dir(&repo_dir)
-                .arg("pull")
-                .arg("--force")
-                .output();
-            if let Err(e) = output {
-                return Err(format!("Failed to pull the remote repository: {}", e));
+            if !is_offline() {
+                let output = std::process::Command::new("git")
+                    .current_dir(&repo_dir)
+                    .arg("pull")
+                    .arg("--force")
+                    .output();
+                if let Err(e) = output {
+                    return Err(format!("Failed to pull the remote repository: {}", e));
+                }
             }
         }
         let package_index_root_fp = repo_dir.join(constants::PACKAGE_INDEX_ROOT_FILE);
         if !package_index_root_fp.exists() {
+            if is_offline() {
+                return Err(format!("Remote {} has no package index ({}) and it can not be refetched in offline mode", self.name, package_index_root_fp.display()));
+            }
             let res = fs::remove_dir_all(&repo_dir);
             if let Err(e) = res {
                 return Err(format!("Unable to remove remote module index repo {}: {}", repo_dir.display(), e));
@@ -547,16 +644,20 @@ pub struct Index {
     pub packages: HashMap<String, (PackageType, Vec<PackageReleaseEntry>)>, // name -> version -> ModuleReleaseEntry
 }
 
+// Sorts by precedence, oldest first. Unparsable versions come before all others, in string order.
+// Platform independent releases come before platform specific releases of the same version,
+// so that the latest release for a platform is the platform specific one if there is any.
 fn sort_version_list(versions: &mut Vec<&PackageReleaseEntry>) {
     versions.sort_by(|a, b| {
         let semver_a = SemVer::parse_from_string(&a.version);
         let semver_b = SemVer::parse_from_string(&b.version);
-        if semver_a.is_none() || semver_b.is_none() {
-            return std::cmp::Ordering::Equal;
-        }
-        let semver_a = semver_a.unwrap();
-        let semver_b = semver_b.unwrap();
-        semver_a.cmp(&semver_b)
+        let by_version = match (semver_a, semver_b) {
+            (Some(semver_a), Some(semver_b)) => semver_a.cmp(&semver_b),
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (None, None) => a.version.cmp(&b.version),
+        };
+        by_version.then_with(|| b.is_platform_independent().cmp(&a.is_platform_independent()))
     });
 }
 
@@ -582,21 +683,20 @@ impl Index {
         let type_versions = self.packages.entry(name.clone()).or_insert((package_type, Vec::new()));
         type_versions.1.push(package);
     }
-    pub fn get_package(&self, name: &str, version: &str) -> Option<(&PackageType, &PackageReleaseEntry)> {
+    pub fn get_package(&self, name: &str, version: &str, platform: &Platform) -> Option<(&PackageType, &PackageReleaseEntry)> {
         let res = self.packages.get(name);
         if res.is_none() {
             return None;
         }
         let (package_type, version_list) = res.unwrap();
-        let platform = get_host_platform().to_string();
-        for module in version_list {
-            if module.version == version && (module.platform.is_none() || module.platform.as_ref().unwrap() == &platform) {
-                return Some((package_type, module));
-            }
-        }
-        None
+        // Prefer the release for the platform over a platform independent one
+        version_list.iter()
+            .filter(|module| module.version == version)
+            .filter_map(|module| platform.match_release_platform(module.platform.as_ref()).map(|rank| (rank, module)))
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, module)| (package_type, module))
     }
-    pub fn get_latest_release(&self, name: &str) -> Option<(&PackageType, &PackageReleaseEntry)> {
+    pub fn get_latest_release(&self, name: &str, platform: &Platform) -> Option<(&PackageType, &PackageReleaseEntry)> {
         let res = self.packages.get(name);
         if res.is_none() {
             return None;
@@ -608,15 +708,29 @@ impl Index {
         if versions.len() == 0 {
             return None;
         }
-        let platform = get_host_platform().to_string();
+        // Pre-releases are never the latest release
         for module in versions {
-            if module.platform.is_none() || module.platform.as_ref().unwrap() == &platform {
+            if module.is_for_platform(platform) && !module.is_pre_release() {
                 return Some((package_type, &module));
             }
         }
         None
     }
-    pub fn get_latest_compatible_release_within_range(&self, name: &str, version_start: &SemVer, version_end: &SemVer) -> Option<(&PackageType, &PackageReleaseEntry)> {
+    // All releases of the package, newest first
+    pub fn get_releases(&self, name: &str) -> Option<(&PackageType, Vec<&PackageReleaseEntry>)> {
+        let (package_type, version_list) = self.packages.get(name)?;
+        let mut versions: Vec<&PackageReleaseEntry> = version_list.iter().collect();
+        sort_version_list(&mut versions);
+        versions.reverse();
+        Some((package_type, versions))
+    }
+    pub fn get_latest_release_matching<F: Fn(&PackageReleaseEntry) -> bool>(&self, name: &str, filter: F) -> Option<(&PackageType, &PackageReleaseEntry)> {
+        let (package_type, version_list) = self.packages.get(name)?;
+        let mut versions: Vec<&PackageReleaseEntry> = version_list.iter().filter(|r| filter(r)).collect();
+        sort_version_list(&mut versions);
+        versions.last().map(|release| (package_type, *release))
+    }
+    pub fn get_latest_compatible_release_within_range(&self, name: &str, version_start: &SemVer, version_end: &SemVer, platform: &Platform) -> Option<(&PackageType, &PackageReleaseEntry)> {
         let res = self.packages.get(name);
         if res.is_none() {
             return None;
@@ -625,17 +739,179 @@ impl Index {
         let mut versions: Vec<&PackageReleaseEntry> = version_list.iter().collect();
         sort_version_list(&mut versions);
         versions.reverse();
-        let platform = get_host_platform().to_string();
         for module in versions {
             let semver = SemVer::parse_from_string(&module.version);
             if semver.is_none() {
-                return None;
+                continue;
             }
             let semver = semver.unwrap();
-            if semver >= *version_start && semver < *version_end && (module.platform.is_none() || module.platform.as_ref().unwrap() == &platform) {
+            if semver >= *version_start && semver < *version_end && !semver.is_pre_release() && module.is_for_platform(platform) {
                 return Some((package_type, module));
             }
         }
         None
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nosman::version_req::BareVersion;
+
+    fn v(s: &str) -> SemVer {
+        SemVer::parse_from_string(s).unwrap()
+    }
+
+    fn req(s: &str) -> VersionReq {
+        VersionReq::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_version_parts() {
+        assert_eq!(v("1.2.3.b4"), SemVer::new(1, Some(2), Some(3), Some(4)));
+        assert_eq!(v("1.2.3.4"), SemVer::new(1, Some(2), Some(3), Some(4)));
+        assert_eq!(v("1.2"), SemVer::new(1, Some(2), None, None));
+        let parsed = v("1.3.0-rc.1+cuda12");
+        assert_eq!(parsed.pre_release, Some("rc.1".to_string()));
+        assert_eq!(parsed.build_metadata, Some("cuda12".to_string()));
+        assert!(parsed.is_pre_release());
+        assert_eq!(parsed.to_string(), "1.3.0-rc.1+cuda12");
+        assert_eq!(v("1.3.0+build-5").pre_release, None);
+    }
+
+    #[test]
+    fn parse_invalid_versions() {
+        assert!(SemVer::parse_from_string("abc").is_none());
+        assert!(SemVer::parse_from_string("1.0.0-").is_none());
+        assert!(SemVer::parse_from_string("1.0.0-rc..1").is_none());
+        assert!(SemVer::parse_from_string("1.0.0+").is_none());
+        assert!(SemVer::parse_from_string("1.0.0-rc_1").is_none());
+    }
+
+    #[test]
+    fn pre_release_precedence() {
+        let ordered = ["1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0", "1.0.1-rc.1", "1.0.1"];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn build_metadata_does_not_affect_precedence() {
+        assert_eq!(v("1.0.0+a").cmp(&v("1.0.0+b")), std::cmp::Ordering::Equal);
+        assert_eq!(v("1.0.0+a").cmp(&v("1.0.0")), std::cmp::Ordering::Equal);
+        assert!(req("=1.0.0").matches(&v("1.0.0+cuda12")));
+    }
+
+    #[test]
+    fn build_number_precedence() {
+        assert!(v("1.2.3.b4") < v("1.2.3.b5"));
+        assert!(v("1.2.3.b9") < v("1.2.4"));
+    }
+
+    #[test]
+    fn same_line_requirements() {
+        assert!(req("1").matches(&v("1.9.9")));
+        assert!(!req("1").matches(&v("2.0.0")));
+        assert!(req("1.2").matches(&v("1.2.0")));
+        assert!(req("1.2").matches(&v("1.2.9")));
+        assert!(!req("1.2").matches(&v("1.3.0")));
+        assert!(!req("1.2").matches(&v("1.1.9")));
+        assert!(req("1.2.3").matches(&v("1.2.3")));
+        assert!(req("1.2.3").matches(&v("1.2.3.b7")));
+        assert!(!req("1.2.3").matches(&v("1.2.4")));
+    }
+
+    #[test]
+    fn same_line_requirements_with_build_numbers() {
+        assert!(req("1.2.3.4").matches(&v("1.2.3.b4")));
+        assert!(!req("1.2.3.4").matches(&v("1.2.3.b5")));
+        assert!(!req("1.2.3.4").matches(&v("1.2.3.b3")));
+        assert!(!req("1.2.3.b4").matches(&v("1.2.4.b4")));
+    }
+
+    #[test]
+    fn caret_requirements() {
+        assert!(req("^1.2").matches(&v("1.2.0")));
+        assert!(req("^1.2").matches(&v("1.9.0")));
+        assert!(!req("^1.2").matches(&v("2.0.0")));
+        assert!(!req("^1.2").matches(&v("1.1.0")));
+        assert!(req("^0.2.3").matches(&v("0.2.9")));
+        assert!(!req("^0.2.3").matches(&v("0.3.0")));
+        assert!(req("^0.0.3").matches(&v("0.0.3")));
+        assert!(!req("^0.0.3").matches(&v("0.0.4")));
+    }
+
+    #[test]
+    fn tilde_requirements() {
+        assert!(req("~1.2.3").matches(&v("1.2.9")));
+        assert!(!req("~1.2.3").matches(&v("1.2.2")));
+        assert!(!req("~1.2.3").matches(&v("1.3.0")));
+        assert!(req("~1").matches(&v("1.5.0")));
+        assert!(!req("~1").matches(&v("2.0.0")));
+    }
+
+    #[test]
+    fn wildcard_requirements() {
+        assert!(req("*").matches(&v("7.1.2")));
+        assert!(req("1.*").matches(&v("1.5.0")));
+        assert!(!req("1.*").matches(&v("2.0.0")));
+        assert!(req("1.2.x").matches(&v("1.2.7")));
+        assert!(!req("1.2.x").matches(&v("1.3.0")));
+        assert!(req("1.2.3.*").matches(&v("1.2.3")));
+        assert!(req("1.2.3.*").matches(&v("1.2.3.b7")));
+        assert!(!req("1.2.3.*").matches(&v("1.2.4")));
+        assert!(!req("1.2.3.*").matches(&v("1.2.5.b0")));
+        assert!(VersionReq::parse("1.2.3.4.*").is_err());
+        assert!(VersionReq::parse("1.*.3").is_err());
+        assert!(VersionReq::parse("a.*").is_err());
+    }
+
+    #[test]
+    fn comma_separated_requirements() {
+        assert!(req(">=1.2, <1.5").matches(&v("1.2.0")));
+        assert!(req(">=1.2, <1.5").matches(&v("1.4.9")));
+        assert!(!req(">=1.2, <1.5").matches(&v("1.5.0")));
+        assert!(req(">1.2.3, <=1.3").matches(&v("1.3.0")));
+        assert!(!req(">1.2.3, <=1.3").matches(&v("1.2.3")));
+        assert!(!req(">1.2.3, <=1.3").matches(&v("1.3.1")));
+    }
+
+    #[test]
+    fn exact_requirements() {
+        assert!(req("=1.2.3").matches(&v("1.2.3")));
+        assert!(!req("=1.2.3").matches(&v("1.2.4")));
+        assert!(req("=1.2").matches(&v("1.2.5")));
+        assert!(!req("=1.2").matches(&v("1.3.0")));
+    }
+
+    #[test]
+    fn pre_releases_only_match_when_asked_for() {
+        assert!(!req("1.3").matches(&v("1.3.1-beta")));
+        assert!(!req("*").matches(&v("1.0.0-rc.1")));
+        assert!(req("1.3").including_pre_releases(true).matches(&v("1.3.1-beta")));
+        assert!(req(">=1.3.0-rc.1").matches(&v("1.3.0-rc.2")));
+        assert!(req(">=1.3.0-rc.1").matches(&v("1.3.0")));
+        assert!(!req(">=1.3.0-rc.1").matches(&v("1.3.0-beta")));
+        assert!(!req(">=1.3.0-rc.1").matches(&v("1.4.0-rc.1")));
+    }
+
+    #[test]
+    fn same_major_requirements() {
+        let sdk = |s: &str| VersionReq::parse_with(s, BareVersion::SameMajor).unwrap();
+        assert!(sdk("1.2.3").matches(&v("1.2.0")));
+        assert!(sdk("1.2.3").matches(&v("1.5.1")));
+        assert!(!sdk("1.2.3").matches(&v("1.1.9")));
+        assert!(!sdk("1.2.3").matches(&v("2.0.0")));
+        assert!(sdk("0.1").matches(&v("0.5.0")));
+        assert!(v("1.5.1").satisfies_requested_version(&v("1.2.3")));
+        assert!(!v("2.0.0").satisfies_requested_version(&v("1.2.3")));
+    }
+
+    #[test]
+    fn invalid_requirements() {
+        assert!(VersionReq::parse("").is_err());
+        assert!(VersionReq::parse("abc").is_err());
+        assert!(VersionReq::parse(">=1.2, <x").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use std::{fs, io};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::nosman::constants;
+use crate::nosman::index::PackageReleaseEntry;
+use crate::nosman::workspace::Workspace;
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct LockedModule {
+    pub name: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    // URL of the remote the module was installed from. None if the module was not installed from a remote.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    // SHA-256 digest of the downloaded archive
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl LockedModule {
+    pub fn local(name: &str, version: &str) -> LockedModule {
+        LockedModule {
+            name: name.to_string(),
+            version: version.to_string(),
+            platform: None,
+            remote: None,
+            url: None,
+            sha256: None,
+        }
+    }
+    pub fn from_release(name: &str, release: &PackageReleaseEntry) -> LockedModule {
+        LockedModule {
+            name: name.to_string(),
+            version: release.version.clone(),
+            platform: release.platform.clone(),
+            remote: release.source_remote.clone(),
+            url: Some(release.url.clone()),
+            sha256: release.sha256.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Lockfile {
+    pub modules: Vec<LockedModule>,
+}
+
+pub fn get_lockfile_path_for(root: &PathBuf) -> PathBuf {
+    root.join(constants::LOCKFILE_NAME)
+}
+
+impl Lockfile {
+    pub fn exists_in(root: &PathBuf) -> bool {
+        get_lockfile_path_for(root).exists()
+    }
+    // Returns an empty lockfile if there is no lockfile under root
+    pub fn load(root: &PathBuf) -> Result<Lockfile, io::Error> {
+        let path = get_lockfile_path_for(root);
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let file = fs::File::open(&path)?;
+        let lockfile: Lockfile = serde_json::from_reader(file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to parse lockfile {}: {}", path.display(), e)))?;
+        Ok(lockfile)
+    }
+    pub fn save(&self, root: &PathBuf) -> Result<(), io::Error> {
+        let file = fs::File::create(get_lockfile_path_for(root))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+    pub fn find(&self, name: &str, version: &str) -> Option<&LockedModule> {
+        self.modules.iter().find(|m| m.name == name && m.version == version)
+    }
+    pub fn find_all(&self, name: &str) -> Vec<&LockedModule> {
+        self.modules.iter().filter(|m| m.name == name).collect()
+    }
+    pub fn set(&mut self, module: LockedModule) {
+        self.modules.retain(|m| !(m.name == module.name && m.version == module.version));
+        self.modules.push(module);
+    }
+    pub fn remove(&mut self, name: &str, version: &str) {
+        self.modules.retain(|m| !(m.name == name && m.version == version));
+    }
+    // Adds the installed modules that are not in the lockfile yet. Modules that were not installed through nosman are recorded without a source.
+    // Entries of modules that are not installed are kept, the lockfile is shared with workspaces that may not have installed them yet.
+    pub fn sync_with(&mut self, workspace: &Workspace) {
+        for (name, versions) in &workspace.installed_modules {
+            for version in versions.keys() {
+                if self.find(name, version).is_none() {
+                    self.modules.push(LockedModule::local(name, version));
+                }
+            }
+        }
+        self.modules.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::nosman::index::ModuleType;
+    use crate::nosman::module::{InstalledModule, ModuleInfo, PackageIdentifier};
+
+    fn locked(name: &str, version: &str) -> LockedModule {
+        LockedModule {
+            url: Some(format!("https://example.com/{}-{}.zip", name, version)),
+            sha256: Some("00".repeat(32)),
+            ..LockedModule::local(name, version)
+        }
+    }
+
+    fn installed(name: &str, version: &str) -> InstalledModule {
+        InstalledModule {
+            info: ModuleInfo {
+                id: PackageIdentifier { name: name.to_string(), version: version.to_string() },
+                display_name: None,
+                description: None,
+                dependencies: None,
+                category: None,
+                tags: None,
+            },
+            manifest_path: PathBuf::from(format!("Module/{}-{}/{}.noscfg", name, version, name)),
+            public_include_folder: None,
+            type_schema_files: vec![],
+            module_type: ModuleType::Plugin,
+            source: None,
+            installed_as_dependency: false,
+        }
+    }
+
+    fn save_lockfile(root: &PathBuf, modules: Vec<LockedModule>) {
+        Lockfile { modules }.save(root).unwrap();
+    }
+
+    #[test]
+    fn update_lockfile_keeps_modules_that_are_not_installed() {
+        // A checked-in lockfile in a workspace that was just initialized
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        save_lockfile(&root, vec![locked("test.a", "1.0.0"), locked("test.b", "2.0.0")]);
+        Workspace::new_empty(root.clone()).update_lockfile().unwrap();
+        let lockfile = Lockfile::load(&root).unwrap();
+        assert_eq!(lockfile.modules, vec![locked("test.a", "1.0.0"), locked("test.b", "2.0.0")]);
+    }
+
+    #[test]
+    fn update_lockfile_keeps_removed_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        save_lockfile(&root, vec![locked("test.a", "1.0.0"), locked("test.b", "2.0.0")]);
+        let mut workspace = Workspace::new_empty(root.clone());
+        workspace.installed_modules.insert("test.b".to_string(), HashMap::from([("2.0.0".to_string(), installed("test.b", "2.0.0"))]));
+        // test.a was removed, it can be installed again with --locked
+        workspace.update_lockfile().unwrap();
+        let lockfile = Lockfile::load(&root).unwrap();
+        assert_eq!(lockfile.find("test.a", "1.0.0"), Some(&locked("test.a", "1.0.0")));
+        assert_eq!(lockfile.find("test.b", "2.0.0"), Some(&locked("test.b", "2.0.0")));
+    }
+
+    #[test]
+    fn update_lockfile_adds_modules_not_installed_from_a_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        save_lockfile(&root, vec![locked("test.b", "2.0.0")]);
+        let mut workspace = Workspace::new_empty(root.clone());
+        workspace.installed_modules.insert("test.a".to_string(), HashMap::from([("1.0.0".to_string(), installed("test.a", "1.0.0"))]));
+        workspace.update_lockfile().unwrap();
+        let lockfile = Lockfile::load(&root).unwrap();
+        assert_eq!(lockfile.modules, vec![LockedModule::local("test.a", "1.0.0"), locked("test.b", "2.0.0")]);
+    }
+
+    #[test]
+    fn remove_drops_only_the_given_version() {
+        let mut lockfile = Lockfile { modules: vec![locked("test.a", "1.0.0"), locked("test.a", "1.1.0")] };
+        lockfile.remove("test.a", "1.0.0");
+        assert_eq!(lockfile.modules, vec![locked("test.a", "1.1.0")]);
+    }
+}
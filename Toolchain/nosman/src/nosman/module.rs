@@ -26,6 +26,32 @@ pub struct ModuleInfo {
     pub tags: Option<Vec<String>>,
 }
 
+// Where a module was installed from, for modules that do not come from a remote
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Hash, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ModuleSource {
+    Git {
+        url: String,
+        // Tag, branch or commit that was requested
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+        commit: String,
+        // Checkout directory, relative to the workspace root. It may contain multiple modules.
+        path: PathBuf,
+    },
+}
+
+impl Display for ModuleSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModuleSource::Git { url, rev, commit, .. } => match rev {
+                Some(rev) => write!(f, "git {}@{} ({})", url, rev, commit),
+                None => write!(f, "git {} ({})", url, commit),
+            },
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Hash, Clone)]
 pub struct InstalledModule {
     pub info: ModuleInfo,
@@ -34,6 +60,11 @@ pub struct InstalledModule {
     pub public_include_folder: Option<PathBuf>,
     pub type_schema_files: Vec<PathBuf>,
     pub module_type: ModuleType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ModuleSource>,
+    // Set for modules that were installed only because another module depends on them, see 'autoremove'
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub installed_as_dependency: bool,
 }
 
 impl Display for InstalledModule {
@@ -75,11 +106,20 @@ impl InstalledModule {
             public_include_folder: None,
             type_schema_files: Vec::new(),
             module_type: ModuleType::Plugin,
+            source: None,
+            installed_as_dependency: false,
         }
     }
     pub fn get_module_dir(&self) -> PathBuf {
         self.manifest_path.parent().unwrap().to_path_buf()
     }
+    // Directory that is removed along with the module: the whole checkout for modules installed from git
+    pub fn get_install_dir(&self) -> PathBuf {
+        match &self.source {
+            Some(ModuleSource::Git { path, .. }) => path.clone(),
+            None => self.get_module_dir(),
+        }
+    }
     pub fn read_manifest(&self) -> Result<serde_json::Value, String> {
         // Read module manifest file as JSON, and read node definition files
         let manifest_file = fs::File::open(&self.manifest_path);
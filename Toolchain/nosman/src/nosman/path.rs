@@ -41,3 +41,13 @@ pub fn get_default_engines_dir(workspace: &PathBuf) -> PathBuf {
     workspace.join("Engine")
 }
 
+
+// Per-user download cache. Can be overridden with an environment variable.
+pub fn get_cache_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var(constants::CACHE_DIR_ENV_VAR) {
+        if !dir.is_empty() {
+            return PathBuf::from(dir);
+        }
+    }
+    dirs::cache_dir().unwrap_or_else(|| std::env::temp_dir()).join("nosman")
+}
@@ -1,32 +1,218 @@
 use std::fmt::{Display, Formatter, Error};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+// Platform of packages that do not contain binaries, e.g. flatbuffers schemas or shader libraries
+pub const ANY_PLATFORM: &str = "any";
+
+// Platforms are written as 'arch-os' with an optional libc/ABI qualifier, e.g. 'x86_64-linux-musl'.
+// Rust style target triples (e.g. 'x86_64-unknown-linux-gnu', 'x86_64-pc-windows-msvc') and common aliases
+// (e.g. 'amd64', 'arm64', 'darwin') are accepted and normalized.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Platform {
     pub arch: String,
     pub os: String,
+    // None if it is the default of the OS (gnu on Linux, msvc on Windows)
+    pub env: Option<String>,
+}
+
+fn normalize_arch(arch: &str) -> String {
+    match arch.to_lowercase().as_str() {
+        "amd64" | "x64" => "x86_64".to_string(),
+        "arm64" => "aarch64".to_string(),
+        "i386" | "i586" | "i686" => "x86".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn normalize_os(os: &str) -> String {
+    match os.to_lowercase().as_str() {
+        "win" | "win32" | "win64" => "windows".to_string(),
+        "darwin" | "osx" | "mac" => "macos".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn default_env(os: &str) -> Option<&'static str> {
+    match os {
+        "linux" => Some("gnu"),
+        "windows" => Some("msvc"),
+        _ => None,
+    }
 }
 
+const VENDORS: [&str; 3] = ["unknown", "pc", "apple"];
+
 impl Platform {
-    pub fn new(arch: String, os: String) -> Platform {
-        Platform { arch, os }
+    pub fn with_env(arch: String, os: String, env: Option<String>) -> Platform {
+        let os = normalize_os(&os);
+        let env = env.map(|e| e.to_lowercase()).filter(|e| Some(e.as_str()) != default_env(&os));
+        Platform { arch: normalize_arch(&arch), os, env }
+    }
+    pub fn any() -> Platform {
+        Platform { arch: ANY_PLATFORM.to_string(), os: ANY_PLATFORM.to_string(), env: None }
+    }
+    pub fn is_any(&self) -> bool {
+        self.arch == ANY_PLATFORM && self.os == ANY_PLATFORM
     }
     pub fn from_str(s: &str) -> Option<Platform> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case(ANY_PLATFORM) {
+            return Some(Platform::any());
+        }
         let parts: Vec<&str> = s.split('-').collect();
-        if parts.len() != 2 {
+        if parts.iter().any(|p| p.is_empty()) {
             return None;
         }
-        Some(Platform::new(parts[0].to_string(), parts[1].to_string()))
+        let (arch, os, env) = match parts.len() {
+            2 => (parts[0], parts[1], None),
+            3 if VENDORS.contains(&parts[1].to_lowercase().as_str()) => (parts[0], parts[2], None),
+            3 => (parts[0], parts[1], Some(parts[2])),
+            4 => (parts[0], parts[2], Some(parts[3])),
+            _ => return None,
+        };
+        Some(Platform::with_env(arch.to_string(), os.to_string(), env.map(|e| e.to_string())))
+    }
+    // Releases for these platforms can be used on this platform, in the order of preference.
+    // Releases for the default env of the OS are used on other envs (e.g. x86_64-windows on x86_64-windows-gnu),
+    // except on musl, which can not load binaries linked against glibc.
+    pub fn fallback_order(&self) -> Vec<Platform> {
+        if self.is_any() {
+            return vec![Platform::any()];
+        }
+        let mut order = vec![self.clone()];
+        if self.env.is_some() && self.env.as_deref() != Some("musl") {
+            order.push(Platform { env: None, ..self.clone() });
+        }
+        order.push(Platform::any());
+        order
+    }
+    // Position of the release platform in the fallback order, lower is preferred.
+    // None if a release for the given platform can not be used on this platform.
+    // Releases without a platform are platform independent.
+    pub fn match_release_platform(&self, release_platform: Option<&String>) -> Option<usize> {
+        let parsed = match release_platform {
+            None => Platform::any(),
+            Some(p) => match Platform::from_str(p) {
+                Some(parsed) => parsed,
+                // Keep exact string matches working for platforms that are not in 'arch-os' form
+                None => return if *p == self.to_string() { Some(0) } else { None },
+            },
+        };
+        self.fallback_order().iter().position(|p| *p == parsed)
     }
 }
 
 impl Display for Platform {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        write!(f, "{}-{}", self.arch, self.os)
+        if self.is_any() {
+            return write!(f, "{}", ANY_PLATFORM);
+        }
+        match &self.env {
+            Some(env) => write!(f, "{}-{}-{}", self.arch, self.os, env),
+            None => write!(f, "{}-{}", self.arch, self.os),
+        }
+    }
+}
+
+fn get_host_env() -> Option<String> {
+    if cfg!(target_env = "musl") {
+        Some("musl".to_string())
+    } else if cfg!(target_env = "gnu") {
+        Some("gnu".to_string())
+    } else if cfg!(target_env = "msvc") {
+        Some("msvc".to_string())
+    } else {
+        None
     }
 }
 
 pub fn get_host_platform() -> Platform {
     let arch = std::env::consts::ARCH;
     let os = std::env::consts::OS;
-    Platform::new(arch.to_string(), os.to_string())
-}
\ No newline at end of file
+    Platform::with_env(arch.to_string(), os.to_string(), get_host_env())
+}
+
+// Parses a platform given on the command line, the host platform if not given
+pub fn parse_platform_or_host(s: Option<&String>) -> Result<Platform, String> {
+    match s {
+        Some(s) => Platform::from_str(s).ok_or(format!("Invalid platform: {}", s)),
+        None => Ok(get_host_platform()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn platform(s: &str) -> Platform {
+        Platform::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn aliases_are_normalized() {
+        assert_eq!(platform("amd64-linux"), platform("x86_64-linux"));
+        assert_eq!(platform("x64-win64"), platform("x86_64-windows"));
+        assert_eq!(platform("arm64-darwin"), platform("aarch64-macos"));
+        assert_eq!(platform("i686-Win32"), platform("x86-windows"));
+        assert_eq!(platform("AMD64-OSX").to_string(), "x86_64-macos");
+    }
+
+    #[test]
+    fn target_triples_are_parsed() {
+        assert_eq!(platform("x86_64-unknown-linux"), platform("x86_64-linux"));
+        assert_eq!(platform("aarch64-apple-darwin"), platform("aarch64-macos"));
+        assert_eq!(platform("x86_64-linux-musl").to_string(), "x86_64-linux-musl");
+        assert_eq!(platform("x86_64-unknown-linux-musl").to_string(), "x86_64-linux-musl");
+        assert_eq!(platform("x86_64-pc-windows-gnu").to_string(), "x86_64-windows-gnu");
+        assert!(Platform::from_str("x86_64").is_none());
+        assert!(Platform::from_str("x86_64--linux").is_none());
+        assert!(Platform::from_str("a-b-c-d-e").is_none());
+    }
+
+    #[test]
+    fn default_env_is_stripped() {
+        assert_eq!(platform("x86_64-unknown-linux-gnu"), platform("x86_64-linux"));
+        assert_eq!(platform("x86_64-pc-windows-msvc"), platform("x86_64-windows"));
+        assert_eq!(platform("x86_64-windows-MSVC").env, None);
+        assert_eq!(Platform::with_env("x86_64".to_string(), "linux".to_string(), Some("gnu".to_string())).to_string(), "x86_64-linux");
+    }
+
+    #[test]
+    fn any_platform() {
+        assert!(platform("any").is_any());
+        assert!(platform("ANY").is_any());
+        assert_eq!(Platform::any().to_string(), ANY_PLATFORM);
+        assert_eq!(Platform::any().fallback_order(), vec![Platform::any()]);
+        assert!(!platform("x86_64-linux").is_any());
+    }
+
+    #[test]
+    fn release_platforms_are_ranked_by_fallback_order() {
+        let linux = platform("x86_64-linux");
+        assert_eq!(linux.match_release_platform(Some(&"x86_64-unknown-linux-gnu".to_string())), Some(0));
+        assert_eq!(linux.match_release_platform(Some(&"any".to_string())), Some(1));
+        assert_eq!(linux.match_release_platform(None), Some(1));
+        assert_eq!(linux.match_release_platform(Some(&"x86_64-windows".to_string())), None);
+        assert_eq!(linux.match_release_platform(Some(&"x86_64-linux-musl".to_string())), None);
+        assert_eq!(linux.match_release_platform(Some(&"aarch64-linux".to_string())), None);
+
+        let windows_gnu = platform("x86_64-windows-gnu");
+        assert_eq!(windows_gnu.match_release_platform(Some(&"x86_64-windows-gnu".to_string())), Some(0));
+        assert_eq!(windows_gnu.match_release_platform(Some(&"x86_64-windows".to_string())), Some(1));
+        assert_eq!(windows_gnu.match_release_platform(None), Some(2));
+
+        let musl = platform("x86_64-linux-musl");
+        assert_eq!(musl.match_release_platform(Some(&"x86_64-linux".to_string())), None);
+        assert_eq!(musl.match_release_platform(None), Some(1));
+
+        // Platforms that are not in 'arch-os' form only match exactly
+        assert_eq!(Platform::any().match_release_platform(Some(&"custom".to_string())), None);
+        assert_eq!(Platform::any().match_release_platform(Some(&"any".to_string())), Some(0));
+    }
+
+    #[test]
+    fn host_platform_matches_its_default_env_releases() {
+        let host = get_host_platform();
+        let default = Platform { env: None, ..host.clone() };
+        assert!(host.match_release_platform(Some(&default.to_string())).is_some() || host.env.as_deref() == Some("musl"));
+    }
+}
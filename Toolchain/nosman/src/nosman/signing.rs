@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::PathBuf;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use colored::Colorize;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use crate::nosman::command::CommandError;
+use crate::nosman::constants;
+use crate::nosman::index::PackageReleaseEntry;
+use crate::nosman::platform::ANY_PLATFORM;
+use crate::nosman::workspace::Workspace;
+
+// Releases are signed with an ed25519 key. The signed message binds the package name, version and platform
+// to the SHA-256 digest (lowercase hex string) of the release archive, so that a signed archive can not be
+// offered as another package, version or platform. Keys and signatures are stored as base64 strings.
+
+pub fn generate_signing_key() -> SigningKey {
+    SigningKey::generate(&mut rand::rngs::OsRng)
+}
+
+pub fn encode_signing_key(key: &SigningKey) -> String {
+    BASE64.encode(key.to_bytes())
+}
+
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    BASE64.encode(key.to_bytes())
+}
+
+// Path given in the command line, or the one in the signing key environment variable
+pub fn get_signing_key_path(arg: Option<&String>) -> Option<PathBuf> {
+    arg.cloned()
+        .or_else(|| std::env::var(constants::SIGNING_KEY_ENV_VAR).ok())
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+}
+
+pub fn load_signing_key(path: &PathBuf) -> Result<SigningKey, CommandError> {
+    let content = fs::read_to_string(path).map_err(|e| CommandError::IOError { file: path.display().to_string(), message: format!("{}", e) })?;
+    let bytes = BASE64.decode(content.trim())
+        .map_err(|e| CommandError::InvalidArgumentError { message: format!("Signing key {} is not valid base64: {}", path.display(), e) })?;
+    let bytes: [u8; 32] = bytes.as_slice().try_into()
+        .map_err(|_| CommandError::InvalidArgumentError { message: format!("Signing key {} should be 32 bytes long", path.display()) })?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+pub fn parse_public_key(key: &str) -> Result<VerifyingKey, String> {
+    let bytes = BASE64.decode(key.trim()).map_err(|e| format!("Public key is not valid base64: {}", e))?;
+    let bytes: [u8; 32] = bytes.as_slice().try_into().map_err(|_| "Public key should be 32 bytes long".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid public key: {}", e))
+}
+
+// Releases without a platform are signed as platform independent
+fn signed_message(name: &str, version: &str, platform: Option<&String>, sha256: &str) -> String {
+    format!("nosman-release\n{}\n{}\n{}\n{}", name, version, platform.map(|p| p.as_str()).unwrap_or(ANY_PLATFORM), sha256)
+}
+
+pub fn sign_release(key: &SigningKey, name: &str, version: &str, platform: Option<&String>, sha256: &str) -> String {
+    BASE64.encode(key.sign(signed_message(name, version, platform, sha256).as_bytes()).to_bytes())
+}
+
+fn verify_message(public_key: &str, message: &str, signature: &Signature) -> bool {
+    match parse_public_key(public_key) {
+        Ok(key) => key.verify(message.as_bytes(), signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+pub struct SignatureCheck {
+    pub remote: String,
+    pub vendor: String,
+    pub name: String,
+    pub version: String,
+    pub platform: Option<String>,
+    pub signature: Option<String>,
+    pub trusted_keys: Vec<String>,
+}
+
+impl SignatureCheck {
+    // Returns None if the remote of the release does not trust any key.
+    // Once a remote trusts a key, every release from it must be signed by a trusted key of the vendor of the package.
+    // Vendor of a package comes from the index, so a release of a vendor without trusted keys can not be installed from that remote.
+    // Releases whose remote is unknown (e.g. in an index cached by an older version) are refused if any remote trusts a key.
+    pub fn for_release(workspace: &Workspace, name: &str, release: &PackageReleaseEntry) -> Result<Option<SignatureCheck>, CommandError> {
+        let remote = release.source_remote.as_ref().and_then(|url| workspace.remotes.iter().find(|r| r.url == *url));
+        if remote.is_none() {
+            Self::check_unknown_release(workspace, &release.url)?;
+        }
+        let vendor = release.source_vendor.clone().unwrap_or_default();
+        if remote.map(|r| r.trusted_keys.is_empty()).unwrap_or(true) {
+            if release.signature.is_some() {
+                println!("{}", format!("Release {} is signed, but its remote does not trust any keys. Signature is not checked.", release.url).yellow());
+            }
+            return Ok(None);
+        }
+        let remote = remote.unwrap();
+        Ok(Some(SignatureCheck {
+            remote: remote.url.clone(),
+            trusted_keys: remote.trusted_keys.iter().filter(|k| k.vendor == vendor).map(|k| k.public_key.clone()).collect(),
+            vendor,
+            name: name.to_string(),
+            version: release.version.clone(),
+            platform: release.platform.clone(),
+            signature: release.signature.clone(),
+        }))
+    }
+
+    // Signature of a release that can not be attributed to a remote can not be checked
+    pub fn check_unknown_release(workspace: &Workspace, url: &str) -> Result<(), CommandError> {
+        if workspace.remotes.iter().any(|r| !r.trusted_keys.is_empty()) {
+            return Err(CommandError::GenericError { message: format!("Remote of release {} is unknown, so its signature can not be checked. Run rescan to update the index.", url) });
+        }
+        Ok(())
+    }
+
+    pub fn verify(&self, url: &str, sha256: &str) -> Result<(), CommandError> {
+        if self.trusted_keys.is_empty() {
+            return Err(CommandError::GenericError { message: format!("Remote {} requires signed releases, but it does not trust any key of vendor '{}' of {}", self.remote, self.vendor, self.name) });
+        }
+        if self.signature.is_none() {
+            return Err(CommandError::GenericError { message: format!("Release {} is not signed, but remote {} requires signatures from vendor '{}'", url, self.remote, self.vendor) });
+        }
+        let bytes = BASE64.decode(self.signature.as_ref().unwrap().trim())
+            .map_err(|e| CommandError::GenericError { message: format!("Signature of {} is not valid base64: {}", url, e) })?;
+        let signature = Signature::from_slice(&bytes)
+            .map_err(|e| CommandError::GenericError { message: format!("Signature of {} is invalid: {}", url, e) })?;
+        let message = signed_message(&self.name, &self.version, self.platform.as_ref(), sha256);
+        if !self.trusted_keys.iter().any(|k| verify_message(k, &message, &signature)) {
+            return Err(CommandError::GenericError { message: format!("Signature verification failed for {}: it is not signed by a trusted key of vendor '{}' for {}-{}", url, self.vendor, self.name, self.version) });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nosman::index::{Remote, TrustedKey};
+
+    const REMOTE_URL: &str = "https://example.com/index";
+
+    fn release(source_remote: Option<&str>, signature: Option<String>) -> PackageReleaseEntry {
+        serde_json::from_value(serde_json::json!({
+            "version": "1.0.0",
+            "url": "https://example.com/test.a-1.0.0.zip",
+            "sha256": "00".repeat(32),
+            "signature": signature,
+            "source_remote": source_remote,
+            "source_vendor": "vendor",
+        })).unwrap()
+    }
+
+    fn workspace_trusting(key: Option<&SigningKey>) -> Workspace {
+        let mut workspace = Workspace::new_empty(PathBuf::from("."));
+        let mut remote = Remote::new("test", REMOTE_URL);
+        if let Some(key) = key {
+            remote.trusted_keys.push(TrustedKey { vendor: "vendor".to_string(), public_key: encode_public_key(&key.verifying_key()) });
+        }
+        workspace.add_remote(remote);
+        workspace
+    }
+
+    #[test]
+    fn release_of_unknown_remote_is_refused_if_any_remote_trusts_a_key() {
+        let key = generate_signing_key();
+        let workspace = workspace_trusting(Some(&key));
+        assert!(SignatureCheck::for_release(&workspace, "test.a", &release(None, None)).is_err());
+        assert!(SignatureCheck::for_release(&workspace, "test.a", &release(Some("https://example.com/other"), None)).is_err());
+        assert!(SignatureCheck::check_unknown_release(&workspace, "https://example.com/test.a-1.0.0.zip").is_err());
+    }
+
+    #[test]
+    fn release_of_unknown_remote_is_not_checked_if_no_remote_trusts_a_key() {
+        let workspace = workspace_trusting(None);
+        assert!(SignatureCheck::for_release(&workspace, "test.a", &release(None, None)).unwrap().is_none());
+        assert!(SignatureCheck::check_unknown_release(&workspace, "https://example.com/test.a-1.0.0.zip").is_ok());
+    }
+
+    #[test]
+    fn signature_is_bound_to_the_release() {
+        let key = generate_signing_key();
+        let workspace = workspace_trusting(Some(&key));
+        let sha256 = "00".repeat(32);
+        let signature = sign_release(&key, "test.a", "1.0.0", None, &sha256);
+        let check = SignatureCheck::for_release(&workspace, "test.a", &release(Some(REMOTE_URL), Some(signature.clone()))).unwrap().unwrap();
+        assert!(check.verify("test.a-1.0.0.zip", &sha256).is_ok());
+        assert!(check.verify("test.a-1.0.0.zip", &"11".repeat(32)).is_err());
+        let other = SignatureCheck::for_release(&workspace, "test.b", &release(Some(REMOTE_URL), Some(signature))).unwrap().unwrap();
+        assert!(other.verify("test.a-1.0.0.zip", &sha256).is_err());
+        let unsigned = SignatureCheck::for_release(&workspace, "test.a", &release(Some(REMOTE_URL), None)).unwrap().unwrap();
+        assert!(unsigned.verify("test.a-1.0.0.zip", &sha256).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt::{Display, Formatter};
+use crate::nosman::index::SemVer;
+
+// Version requirements, e.g. '1.2', '^1.2', '~1.2.3', '>=1.2, <2', '1.*' or '=1.2.3'.
+// A version without an operator keeps its traditional meaning ('a.b <= x < a.(b+1)', see BareVersion).
+// Pre-releases (e.g. 1.3.0-rc.1) are excluded unless they are asked for.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Bound {
+    Ge(SemVer),
+    Gt(SemVer),
+    Le(SemVer),
+    Lt(SemVer),
+}
+
+impl Bound {
+    fn version(&self) -> &SemVer {
+        match self {
+            Bound::Ge(v) | Bound::Gt(v) | Bound::Le(v) | Bound::Lt(v) => v,
+        }
+    }
+}
+
+// How a version without an operator is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BareVersion {
+    // '1.2' means '>=1.2, <1.3', '1.2.3' means '>=1.2.3, <1.2.4' and '1' means '>=1, <2'
+    SameLine,
+    // Same major version with an equal or newer minor version, patch is ignored: '1.2.3' means '>=1.2, <2'.
+    // Used for SDK and API versions.
+    SameMajor,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    raw: String,
+    bounds: Vec<Bound>,
+    // Whether pre-releases match without being mentioned in the requirement
+    include_pre: bool,
+}
+
+// Missing minor and patch components are zero when comparing against a bound
+fn normalize(v: &SemVer) -> SemVer {
+    SemVer {
+        minor: Some(v.minor.unwrap_or(0)),
+        patch: Some(v.patch.unwrap_or(0)),
+        ..v.clone()
+    }
+}
+
+fn version(major: u32, minor: u32, patch: u32) -> SemVer {
+    SemVer::new(major, Some(minor), Some(patch), None)
+}
+
+fn caret(v: &SemVer) -> Vec<Bound> {
+    let upper = match (v.major, v.minor, v.patch) {
+        (0, Some(0), Some(patch)) => version(0, 0, patch + 1),
+        (0, Some(minor), _) => version(0, minor + 1, 0),
+        (major, _, _) => version(major + 1, 0, 0),
+    };
+    vec![Bound::Ge(normalize(v)), Bound::Lt(upper)]
+}
+
+fn tilde(v: &SemVer) -> Vec<Bound> {
+    let upper = match v.minor {
+        Some(minor) => version(v.major, minor + 1, 0),
+        None => version(v.major + 1, 0, 0),
+    };
+    vec![Bound::Ge(normalize(v)), Bound::Lt(upper)]
+}
+
+fn same_line(v: &SemVer) -> Vec<Bound> {
+    let upper = match (v.minor, v.patch, v.build_number) {
+        (None, _, _) => version(v.major + 1, 0, 0),
+        (Some(minor), None, _) => version(v.major, minor + 1, 0),
+        (Some(_), Some(_), None) => normalize(&v.upper_patch()),
+        (Some(_), Some(_), Some(_)) => v.upper_build(),
+    };
+    vec![Bound::Ge(normalize(v)), Bound::Lt(upper)]
+}
+
+fn same_major(v: &SemVer) -> Vec<Bound> {
+    vec![Bound::Ge(version(v.major, v.minor.unwrap_or(0), 0)), Bound::Lt(version(v.major + 1, 0, 0))]
+}
+
+// '=1.2' matches every 1.2.x, '=1.2.3' only 1.2.3
+fn exact(v: &SemVer) -> Vec<Bound> {
+    match (v.minor, v.patch) {
+        (None, _) => vec![Bound::Ge(normalize(v)), Bound::Lt(version(v.major + 1, 0, 0))],
+        (Some(minor), None) => vec![Bound::Ge(normalize(v)), Bound::Lt(version(v.major, minor + 1, 0))],
+        _ => vec![Bound::Ge(normalize(v)), Bound::Le(normalize(v))],
+    }
+}
+
+// '*', '1.*', '1.2.*', '1.2.3.*' for every build of 1.2.3 (or 'x' instead of '*')
+fn wildcard(s: &str) -> Option<Result<Vec<Bound>, String>> {
+    let parts: Vec<&str> = s.split('.').collect();
+    let is_wildcard = |p: &&str| *p == "*" || *p == "x" || *p == "X";
+    let first = parts.iter().position(is_wildcard)?;
+    if parts[first..].iter().any(|p| !is_wildcard(p)) {
+        return Some(Err(format!("Invalid wildcard version: {}", s)));
+    }
+    let numbers: Result<Vec<u32>, _> = parts[..first].iter().map(|p| p.parse::<u32>()).collect();
+    if numbers.is_err() {
+        return Some(Err(format!("Invalid wildcard version: {}", s)));
+    }
+    let numbers = numbers.unwrap();
+    Some(Ok(match numbers.len() {
+        0 => vec![],
+        1 => vec![Bound::Ge(version(numbers[0], 0, 0)), Bound::Lt(version(numbers[0] + 1, 0, 0))],
+        2 => vec![Bound::Ge(version(numbers[0], numbers[1], 0)), Bound::Lt(version(numbers[0], numbers[1] + 1, 0))],
+        3 => vec![Bound::Ge(version(numbers[0], numbers[1], numbers[2])), Bound::Lt(version(numbers[0], numbers[1], numbers[2] + 1))],
+        _ => return Some(Err(format!("Invalid wildcard version: {}", s))),
+    }))
+}
+
+fn parse_version(s: &str, req: &str) -> Result<SemVer, String> {
+    SemVer::parse_from_string(s.trim()).ok_or(format!("Invalid version requirement: {}", req))
+}
+
+impl VersionReq {
+    pub fn parse(s: &str) -> Result<VersionReq, String> {
+        Self::parse_with(s, BareVersion::SameLine)
+    }
+
+    pub fn parse_with(s: &str, bare: BareVersion) -> Result<VersionReq, String> {
+        let mut bounds = Vec::new();
+        if s.trim().is_empty() {
+            return Err("Empty version requirement".to_string());
+        }
+        for part in s.split(',') {
+            let part = part.trim();
+            if let Some(res) = wildcard(part) {
+                bounds.extend(res?);
+            } else if let Some(rest) = part.strip_prefix(">=") {
+                bounds.push(Bound::Ge(normalize(&parse_version(rest, s)?)));
+            } else if let Some(rest) = part.strip_prefix("<=") {
+                bounds.push(Bound::Le(normalize(&parse_version(rest, s)?)));
+            } else if let Some(rest) = part.strip_prefix('>') {
+                bounds.push(Bound::Gt(normalize(&parse_version(rest, s)?)));
+            } else if let Some(rest) = part.strip_prefix('<') {
+                bounds.push(Bound::Lt(normalize(&parse_version(rest, s)?)));
+            } else if let Some(rest) = part.strip_prefix('=') {
+                bounds.extend(exact(&parse_version(rest, s)?));
+            } else if let Some(rest) = part.strip_prefix('^') {
+                bounds.extend(caret(&parse_version(rest, s)?));
+            } else if let Some(rest) = part.strip_prefix('~') {
+                bounds.extend(tilde(&parse_version(rest, s)?));
+            } else {
+                let v = parse_version(part, s)?;
+                bounds.extend(match bare {
+                    BareVersion::SameLine => same_line(&v),
+                    BareVersion::SameMajor => same_major(&v),
+                });
+            }
+        }
+        Ok(VersionReq { raw: s.trim().to_string(), bounds, include_pre: false })
+    }
+
+    // Versions an engine must provide to satisfy a required SDK or API version, see BareVersion::SameMajor
+    pub fn same_major_as(v: &SemVer) -> VersionReq {
+        VersionReq { raw: v.to_string(), bounds: same_major(v), include_pre: true }
+    }
+
+    pub fn including_pre_releases(mut self, include_pre: bool) -> VersionReq {
+        self.include_pre = include_pre;
+        self
+    }
+
+    pub fn matches(&self, v: &SemVer) -> bool {
+        let v = normalize(v);
+        // Unless pre-releases are included, a pre-release only matches if the requirement mentions a pre-release
+        // of the same version, e.g. '>=1.3.0-rc.1' matches 1.3.0-rc.2 but not 1.4.0-rc.1
+        if v.is_pre_release() && !self.include_pre {
+            let mentioned = self.bounds.iter().map(|b| b.version())
+                .any(|b| b.is_pre_release() && b.major == v.major && b.minor == v.minor && b.patch == v.patch);
+            if !mentioned {
+                return false;
+            }
+        }
+        self.bounds.iter().all(|bound| match bound {
+            Bound::Ge(b) => v >= *b,
+            Bound::Gt(b) => v > *b,
+            Bound::Le(b) => v <= *b,
+            Bound::Lt(b) => v < *b,
+        })
+    }
+
+    // Smallest versions allowed by each inclusive lower bound
+    pub fn lower_bounds(&self) -> Vec<SemVer> {
+        self.bounds.iter().filter_map(|b| match b {
+            Bound::Ge(v) => Some(v.clone()),
+            _ => None,
+        }).collect()
+    }
+
+    pub fn matches_str(&self, v: &str) -> bool {
+        SemVer::parse_from_string(v).map(|v| self.matches(&v)).unwrap_or(false)
+    }
+}
+
+impl Display for VersionReq {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
@@ -1,4 +1,5 @@
-use std::collections::{HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::{fs, io};
 use std::cmp::PartialEq;
 use std::path::PathBuf;
@@ -9,10 +10,13 @@ use colored::Colorize;
 use indicatif::{ProgressBar};
 use serde::{Deserialize, Serialize};
 use crate::nosman::command::{CommandError, CommandResult};
-use crate::nosman::{constants};
-use crate::nosman::index::{Index, PackageIndexEntry, PackageReleases, Remote, SemVer};
-use crate::nosman::module::{InstalledModule, get_module_manifests, NodeDefinition};
+use crate::nosman::{constants, git};
+use crate::nosman::common::{http_client, is_offline};
+use crate::nosman::lockfile::Lockfile;
+use crate::nosman::index::{Index, PackageIndexEntry, PackageReleaseEntry, PackageReleases, PackageType, Remote, SemVer};
+use crate::nosman::module::{InstalledModule, get_module_manifests, ModuleSource, NodeDefinition, PackageIdentifier};
 use crate::nosman::path::get_rel_path_based_on;
+use crate::nosman::version_req::VersionReq;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Workspace {
@@ -103,47 +107,150 @@ impl Workspace {
         }
         res
     }
-    pub fn get_latest_installed_module_within_range(&self, name: &str, version_start: &SemVer, version_end: &SemVer) -> Option<&InstalledModule> {
-        let version_list = self.installed_modules.get(name);
-        if version_list.is_none() {
-            return None;
-        }
-        let version_list = version_list.unwrap();
-        let mut versions: Vec<(&String, &InstalledModule)> = version_list.iter().collect();
-        versions.sort_by(|a, b| a.0.cmp(b.0));
-        versions.reverse();
-        for (version, module) in versions {
-            let semver = SemVer::parse_from_string(version);
-            if semver.is_none() {
-                return None;
+    pub fn get_latest_installed_module_matching(&self, name: &str, req: &VersionReq) -> Option<&InstalledModule> {
+        let version_list = self.installed_modules.get(name)?;
+        version_list.iter()
+            .filter_map(|(version, module)| SemVer::parse_from_string(version).map(|v| (v, module)))
+            .filter(|(v, _)| req.matches(v))
+            .max_by(|a, b| a.0.cmp(&b.0))
+            .map(|(_, module)| module)
+    }
+    // requested_version is a version requirement, see VersionReq
+    pub fn get_latest_installed_module_for_version(&self, module_name: &str, requested_version: &str) -> Result<&InstalledModule, String> {
+        let req = VersionReq::parse(requested_version)?;
+        let res = self.get_latest_installed_module_matching(module_name, &req);
+        if res.is_none() {
+            return Err(format!("No installed version matching {} for module {}", req, module_name));
+        }
+        Ok(res.unwrap())
+    }
+    // Returns the dependencies for which no single version satisfies every module requiring it.
+    // Modules in additional (with their dependencies) are treated as installed, e.g. the modules about to be installed.
+    pub fn find_dependency_conflicts(&self, additional: &Vec<(PackageIdentifier, Vec<PackageIdentifier>)>) -> Vec<DependencyConflict> {
+        let mut modules: Vec<(PackageIdentifier, Vec<PackageIdentifier>)> = self.installed_modules.values().flat_map(|versions| versions.values())
+            .map(|m| (m.info.id.clone(), m.info.dependencies.clone().unwrap_or_default()))
+            .collect();
+        for (id, deps) in additional {
+            if self.get_installed_module(&id.name, &id.version).is_none() {
+                modules.push((id.clone(), deps.clone()));
+            }
+        }
+        // Older versions kept side by side (e.g. after upgrade) are superseded, only the newest version of each module counts
+        let mut newest: HashMap<String, &(PackageIdentifier, Vec<PackageIdentifier>)> = HashMap::new();
+        for module in &modules {
+            let is_newer = match newest.get(&module.0.name) {
+                Some(current) => SemVer::parse_from_string(&module.0.version) > SemVer::parse_from_string(&current.0.version),
+                None => true,
+            };
+            if is_newer {
+                newest.insert(module.0.name.clone(), module);
             }
-            let semver = semver.unwrap();
-            if semver >= *version_start && semver < *version_end {
-                return Some(module);
+        }
+        let requirers: Vec<&(PackageIdentifier, Vec<PackageIdentifier>)> = newest.into_values().collect();
+        let mut requirements: BTreeMap<String, Vec<(PackageIdentifier, String)>> = BTreeMap::new();
+        for (id, deps) in &requirers {
+            for dep in deps.iter() {
+                requirements.entry(dep.name.clone()).or_default().push((id.clone(), dep.version.clone()));
             }
         }
-        None
+        let mut conflicts = Vec::new();
+        for (name, mut reqs) in requirements {
+            let parsed: Vec<VersionReq> = reqs.iter().filter_map(|(_, r)| VersionReq::parse(r).ok()).collect();
+            if parsed.len() < 2 {
+                continue;
+            }
+            // Any installed, available or requested version satisfying all of them resolves the conflict
+            let mut candidates: Vec<SemVer> = Vec::new();
+            candidates.extend(self.installed_modules.get(&name).map(|v| v.keys().filter_map(|v| SemVer::parse_from_string(v)).collect::<Vec<SemVer>>()).unwrap_or_default());
+            if let Some((_, releases)) = self.index_cache.packages.get(&name) {
+                candidates.extend(releases.iter().filter_map(|r| SemVer::parse_from_string(&r.version)));
+            }
+            candidates.extend(modules.iter().filter(|(id, _)| id.name == name).filter_map(|(id, _)| SemVer::parse_from_string(&id.version)));
+            candidates.extend(parsed.iter().flat_map(|r| r.lower_bounds()));
+            if candidates.iter().any(|c| parsed.iter().all(|r| r.matches(c))) {
+                continue;
+            }
+            reqs.sort_by(|a, b| a.0.name.cmp(&b.0.name).then(a.0.version.cmp(&b.0.version)));
+            conflicts.push(DependencyConflict { dependency: name, requirements: reqs });
+        }
+        conflicts
     }
-    pub fn get_latest_installed_module_for_version(&self, module_name: &str, requested_version: &str) -> Result<&InstalledModule, String> {
-        let semver_res = SemVer::parse_from_string(requested_version);
-        if semver_res.is_none() {
-            return Err(format!("Invalid semantic version: {}.", requested_version));
+    // Returns the installed modules that would have a missing dependency if the given modules were removed.
+    // A dependency is missing if no other installed version matches its version requirement.
+    pub fn get_dependents(&self, removed: &HashSet<PackageIdentifier>) -> Vec<PackageIdentifier> {
+        let mut dependents = Vec::new();
+        for versions in self.installed_modules.values() {
+            for module in versions.values() {
+                if removed.contains(&module.info.id) {
+                    continue;
+                }
+                let broken = module.info.dependencies.as_ref().unwrap_or(&vec![]).iter().any(|dep| {
+                    let req = VersionReq::parse(&dep.version);
+                    if req.is_err() {
+                        return false;
+                    }
+                    let req = req.unwrap();
+                    let matching: Vec<&String> = self.installed_modules.get(&dep.name).map(|v| v.keys().filter(|v| req.matches_str(v)).collect()).unwrap_or_default();
+                    !matching.is_empty() && matching.iter().all(|v| removed.contains(&PackageIdentifier { name: dep.name.clone(), version: (*v).clone() }))
+                });
+                if broken {
+                    dependents.push(module.info.id.clone());
+                }
+            }
         }
-        let version_start = semver_res.unwrap();
-        if version_start.minor.is_none() {
-            return Err("Please provide a minor version too!".to_string());
+        dependents.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+        dependents
+    }
+    // Returns the installed modules reachable from the given modules through their dependencies, including themselves
+    pub fn get_installed_dependency_closure(&self, roots: &Vec<PackageIdentifier>) -> HashSet<PackageIdentifier> {
+        let mut reachable = HashSet::new();
+        let mut stack = roots.clone();
+        while let Some(id) = stack.pop() {
+            if !reachable.insert(id.clone()) {
+                continue;
+            }
+            if let Some(module) = self.get_installed_module(&id.name, &id.version) {
+                for dep in module.info.dependencies.clone().unwrap_or_default() {
+                    if let Ok(dep_module) = self.get_latest_installed_module_for_version(&dep.name, &dep.version) {
+                        stack.push(dep_module.info.id.clone());
+                    }
+                }
+            }
         }
-        let version_end = version_start.get_one_up();
-        let res = self.get_latest_installed_module_within_range(module_name, &version_start, &version_end);
-        if res.is_none() {
-            return Err(format!("No installed version in range [{}, {}) for module {}", version_start.to_string(), version_end.to_string(), module_name));
+        reachable
+    }
+    pub fn set_installed_as_dependency(&mut self, name: &str, version: &str, installed_as_dependency: bool) {
+        if let Some(module) = self.installed_modules.get_mut(name).and_then(|versions| versions.get_mut(version)) {
+            module.installed_as_dependency = installed_as_dependency;
         }
-        Ok(res.unwrap())
+    }
+    // Returns the modules that were installed as a dependency and are not reachable from any explicitly installed module
+    pub fn get_orphaned_modules(&self) -> Vec<PackageIdentifier> {
+        let roots: Vec<PackageIdentifier> = self.installed_modules.values().flat_map(|versions| versions.values())
+            .filter(|m| !m.installed_as_dependency)
+            .map(|m| m.info.id.clone())
+            .collect();
+        let reachable = self.get_installed_dependency_closure(&roots);
+        let mut orphans: Vec<PackageIdentifier> = self.installed_modules.values().flat_map(|versions| versions.values())
+            .filter(|m| m.installed_as_dependency && !reachable.contains(&m.info.id))
+            .map(|m| m.info.id.clone())
+            .collect();
+        orphans.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+        orphans
     }
     pub fn add(&mut self, module: InstalledModule) {
         let versions = self.installed_modules.entry(module.info.id.name.clone()).or_insert(HashMap::new());
         versions.insert(module.info.id.version.clone(), module);
     }
+    // Removes the modules in order. A module in the same git checkout as a removed one is already removed with it.
+    pub fn remove_modules(&mut self, ids: &Vec<PackageIdentifier>) -> CommandResult {
+        for id in ids {
+            if self.get_installed_module(&id.name, &id.version).is_some() {
+                self.remove(&id.name, &id.version)?;
+            }
+        }
+        Ok(true)
+    }
     pub fn remove(&mut self, name: &str, version: &str) -> CommandResult {
         let res = self.get_installed_module(name, version);
         if res.is_none() {
@@ -151,11 +258,30 @@ impl Workspace {
         }
         println!("Removing module {} version {}", name, version);
         let module = res.unwrap();
-        fs::remove_dir_all(module.get_module_dir())?;
+        let install_dir = module.get_install_dir();
+        if let Some(source) = &module.source {
+            println!("{}", format!("Removing {} installed from {}", install_dir.display(), source).yellow());
+            if git::has_local_changes(&self.root.join(&install_dir)) {
+                println!("{}", format!("{} has local changes, they will be lost", install_dir.display()).yellow());
+            }
+        }
+        fs::remove_dir_all(self.root.join(&install_dir))?;
         if let Some(versions) = self.installed_modules.get_mut(name) {
             versions.remove(version);
         }
+        // Other modules in the same checkout are gone too
+        for (other_name, versions) in self.installed_modules.iter_mut() {
+            versions.retain(|other_version, other| {
+                let removed = other.source.is_some() && other.get_install_dir() == install_dir;
+                if removed {
+                    println!("Removing module {} version {} from the same checkout", other_name, other_version);
+                }
+                !removed
+            });
+        }
+        self.installed_modules.retain(|_, versions| !versions.is_empty());
         self.save()?;
+        self.update_lockfile()?;
         println!("{}", format!("Module {} version {} removed successfully", name, version).as_str().green());
         Ok(true)
     }
@@ -163,11 +289,15 @@ impl Workspace {
         for (_name, versions) in self.installed_modules.iter() {
             for (_version, module) in versions.iter() {
                 println!("Removing module {}", module.info.id);
-                fs::remove_dir_all(module.get_module_dir())?;
+                let install_dir = self.root.join(module.get_install_dir());
+                if install_dir.exists() {
+                    fs::remove_dir_all(install_dir)?;
+                }
             }
         }
         self.installed_modules.clear();
         self.save()?;
+        self.update_lockfile()?;
         println!("{}", "All modules removed successfully".green());
         Ok(true)
     }
@@ -181,7 +311,12 @@ impl Workspace {
 
         pb.println(format!("Found {} modules in {}", module_manifests.len(), folder.display()).as_str().green().to_string());
 
+        // Modules being installed are staged under .nosman, they are not a part of the workspace yet
+        let nosman_dir = dunce::canonicalize(get_nosman_dir_for(&self.root)).unwrap_or(get_nosman_dir_for(&self.root));
         for (ty, path) in module_manifests {
+            if path.starts_with(&nosman_dir) {
+                continue;
+            }
             pb.set_message(format!("Scanning module: {}", path.display()));
             let file = match fs::File::open(&path) {
                 Ok(file) => file,
@@ -233,6 +368,25 @@ impl Workspace {
             self.add(installed_module);
         }
     }
+    // Registers the modules in a git checkout, replacing the entries of a previous checkout at the same path
+    pub fn register_git_checkout(&mut self, checkout_dir: &PathBuf, url: &str, rev: Option<&String>, commit: &str) -> Vec<PackageIdentifier> {
+        let path = get_rel_path_based_on(checkout_dir, &self.root);
+        for versions in self.installed_modules.values_mut() {
+            versions.retain(|_, m| !(m.source.is_some() && m.get_install_dir() == path));
+        }
+        self.installed_modules.retain(|_, versions| !versions.is_empty());
+        self.scan_modules_in_folder(checkout_dir.clone(), true);
+        let mut registered = Vec::new();
+        for versions in self.installed_modules.values_mut() {
+            for module in versions.values_mut() {
+                if module.manifest_path.starts_with(&path) {
+                    module.source = Some(ModuleSource::Git { url: url.to_string(), rev: rev.cloned(), commit: commit.to_string(), path: path.clone() });
+                    registered.push(module.info.id.clone());
+                }
+            }
+        }
+        registered
+    }
     pub fn scan_modules(&mut self, force_replace_in_registry: bool) {
        self.scan_modules_in_folder(self.root.clone(), force_replace_in_registry);
     }
@@ -244,16 +398,46 @@ impl Workspace {
     }
     pub fn rescan(&mut self, flags: RescanFlags) -> CommandResult {
         if flags.contains(RescanFlags::FetchPackageIndex) {
-            self.index_cache.packages.clear();
-            self.fetch_remotes(flags.contains(RescanFlags::AddDefaultPackageIndexIfNoRemoteExists))?;
+            if is_offline() {
+                println!("{}", "Offline mode: using the cached package index".yellow());
+            } else {
+                self.index_cache.packages.clear();
+                self.fetch_remotes(flags.contains(RescanFlags::AddDefaultPackageIndexIfNoRemoteExists))?;
+            }
         }
         if flags.contains(RescanFlags::ScanModules) {
+            // Sources and markings are not in the manifests, keep them for the modules that are still there
+            let previous: HashMap<PathBuf, (Option<ModuleSource>, bool)> = self.installed_modules.values().flat_map(|versions| versions.values())
+                .map(|m| (m.manifest_path.clone(), (m.source.clone(), m.installed_as_dependency)))
+                .collect();
             self.installed_modules.clear();
             self.scan_modules(true);
+            for versions in self.installed_modules.values_mut() {
+                for module in versions.values_mut() {
+                    if let Some((source, installed_as_dependency)) = previous.get(&module.manifest_path) {
+                        module.source = source.clone();
+                        module.installed_as_dependency = *installed_as_dependency;
+                    }
+                }
+            }
         }
         self.save()?;
+        if flags.contains(RescanFlags::ScanModules) {
+            self.update_lockfile()?;
+        }
         Ok(true)
     }
+    pub fn update_lockfile(&self) -> Result<(), io::Error> {
+        let mut lockfile = Lockfile::load(&self.root)?;
+        lockfile.sync_with(self);
+        lockfile.save(&self.root)
+    }
+    // Removing a module keeps its lockfile entry, only an explicit replacement (e.g. upgrade) drops it
+    pub fn remove_from_lockfile(&self, name: &str, version: &str) -> Result<(), io::Error> {
+        let mut lockfile = Lockfile::load(&self.root)?;
+        lockfile.remove(name, version);
+        lockfile.save(&self.root)
+    }
     pub fn fetch_remotes(&mut self, add_default_remote: bool) -> Result<(), io::Error>{
         if self.remotes.is_empty() {
             if add_default_remote {
@@ -266,9 +450,29 @@ impl Workspace {
         self.save()
     }
     pub fn fetch_package_releases(&mut self, package_name: &str) {
+        self.fetch_package_releases_of(&vec![package_name.to_string()]);
+    }
+    pub fn fetch_package_releases_of(&mut self, package_names: &Vec<String>) {
+        if is_offline() {
+            for name in package_names {
+                if !self.index_cache.packages.contains_key(name) {
+                    println!("{}", format!("No cached releases found for package {} and nosman is in offline mode", name).yellow());
+                }
+            }
+            return;
+        }
         let pb = ProgressBar::new_spinner();
         pb.enable_steady_tick(Duration::from_millis(100));
-        pb.set_message(format!("Fetching package index for {}", package_name));
+        pb.set_message(format!("Fetching package index for {}", package_names.join(", ")));
+        let client = http_client();
+        if let Err(e) = client {
+            pb.finish_and_clear();
+            eprintln!("{}", e);
+            return;
+        }
+        let client = client.unwrap();
+        // Releases are collected first, so that a failed fetch does not wipe the cached entries of a package
+        let mut fetched: HashMap<String, (PackageType, Vec<PackageReleaseEntry>)> = HashMap::new();
         for remote in &self.remotes {
             pb.set_message(format!("Fetching remote {}", remote.name));
             let res = remote.fetch(&self);
@@ -280,27 +484,36 @@ impl Workspace {
             pb.println(format!("Fetched {} packages from remote {}", package_list.len(), remote.name));
             // For each module in list
             for package in package_list {
-                if package.name != *package_name {
+                if !package_names.contains(&package.name) {
                     continue;
                 }
-                let res = reqwest::blocking::get(&package.releases_url);
+                let res = client.get(&package.releases_url).send();
                 if let Err(e) = res {
-                    pb.println(format!("Failed to fetch package releases for {}: {}", package_name, e));
+                    pb.println(format!("Failed to fetch package releases for {}: {}", package.name, e));
                     continue;
                 }
                 let res = res.unwrap().json();
                 if let Err(e) = res {
-                    pb.println(format!("Failed to parse package releases for {}: {}", package_name, e));
+                    pb.println(format!("Failed to parse package releases for {}: {}", package.name, e));
                     continue;
                 }
                 let versions: PackageReleases = res.unwrap();
                 pb.set_message(format!("Remote {}: Found {} releases for package {}", remote.name, versions.releases.len(), versions.name));
-                // For each version in list
-                for release in versions.releases {
-                    self.index_cache.add_package(&versions.name, package.package_type.clone(), release);
+                let type_releases = fetched.entry(versions.name.clone()).or_insert((package.package_type.clone(), Vec::new()));
+                for mut release in versions.releases {
+                    release.source_remote = Some(remote.url.clone());
+                    release.source_vendor = Some(package.vendor.clone());
+                    type_releases.1.push(release);
                 }
             }
         }
+        for (name, (package_type, releases)) in fetched {
+            self.index_cache.packages.remove(&name);
+            for release in releases {
+                self.index_cache.add_package(&name, package_type.clone(), release);
+            }
+        }
+        pb.finish_and_clear();
     }
     pub fn get_node_definitions(&self, node_class_name: &String) -> Vec<NodeDefinition> {
         let mut res = Vec::new();
@@ -315,6 +528,19 @@ impl Workspace {
     }
 }
 
+pub struct DependencyConflict {
+    pub dependency: String,
+    // Modules requiring the dependency, with their version requirements
+    pub requirements: Vec<(PackageIdentifier, String)>,
+}
+
+impl Display for DependencyConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reqs: Vec<String> = self.requirements.iter().map(|(id, req)| format!("{} requires {}", id, req)).collect();
+        write!(f, "No version of {} satisfies all modules: {}", self.dependency, reqs.join(", "))
+    }
+}
+
 pub fn find_root_from(path: &PathBuf) -> Option<PathBuf> {
     let mut current = path.clone();
     loop {
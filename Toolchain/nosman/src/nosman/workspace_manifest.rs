@@ -0,0 +1,35 @@
+use std::{fs, io};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::nosman::constants;
+use crate::nosman::index::Remote;
+use crate::nosman::module::PackageIdentifier;
+
+// Checked-in description of the desired state of a workspace. See 'sync' command.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkspaceManifest {
+    // Nodos release to bring under the workspace. Version is interpreted the same way as 'get --version'.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nodos: Option<PackageIdentifier>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remotes: Vec<Remote>,
+    // Versions are interpreted the same way as module dependencies: 'a.b <= x < a.(b+1)'
+    #[serde(default)]
+    pub modules: Vec<PackageIdentifier>,
+}
+
+pub fn get_workspace_manifest_path_for(root: &PathBuf) -> PathBuf {
+    root.join(constants::WORKSPACE_MANIFEST_FILE_NAME)
+}
+
+impl WorkspaceManifest {
+    pub fn exists_in(root: &PathBuf) -> bool {
+        get_workspace_manifest_path_for(root).exists()
+    }
+    pub fn load(root: &PathBuf) -> Result<WorkspaceManifest, io::Error> {
+        let path = get_workspace_manifest_path_for(root);
+        let file = fs::File::open(&path)?;
+        serde_json::from_reader(file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to parse workspace manifest {}: {}", path.display(), e)))
+    }
+}